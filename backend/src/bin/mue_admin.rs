@@ -0,0 +1,294 @@
+// backend/src/bin/mue_admin.rs
+// Offline operator tool: works on the log files directly, no server needed.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::File,
+    io::{self, Write},
+    path::PathBuf,
+    process::ExitCode,
+};
+
+use backend::engine::rules::RulesHandle;
+use backend::engine::season::SeasonCalendar;
+use backend::engine::tier::TierTable;
+use backend::handlers::leaderboard::{build_leaderboard, LeaderboardEntry, Ranking};
+use backend::handlers::submission::{replay_into_sessions, ReplayReport};
+use backend::state::{pki::PubKey, root::{session_roots, state_root}, snapshot, Guilds, Sessions};
+use backend::store::{self, chain::verify_chain, rules_history::{history_path, RulesHistory}, SubmissionStore};
+
+const USAGE: &str = "\
+usage: mue-admin [--store jsonl|sqlite] [--log PATH] <command> [options]
+
+commands:
+  verify                           check checksums and the hash chain
+  replay                           replay the log through the engine, print final scores
+  diff [--snapshot FILE]           compare snapshot + tail replay against a full replay
+                                   (default: newest snapshot in SNAPSHOT_DIR)
+  export [--format csv|json] [--out FILE] [--season ID]
+                                   write the replayed leaderboard (default json to stdout,
+                                   all-time unless a season from SEASONS_PATH is given)
+
+--store / --log default to SUBMISSION_STORE / SUBMISSION_STORE_PATH; records
+without a rules id are re-scored under SCORING_RULES_PATH (or the built-in table),
+records with one follow the bonus rules kept for it in RULES_HISTORY_PATH.
+exit status: 0 ok, 1 problem found, 2 usage or I/O error";
+
+struct Args {
+    store: String,
+    log: Option<String>,
+    command: String,
+    snapshot: Option<PathBuf>,
+    format: String,
+    out: Option<PathBuf>,
+    season: Option<String>,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let (store, log) = store::location_from_env();
+    let mut args = Args {
+        store,
+        log,
+        command: String::new(),
+        snapshot: None,
+        format: "json".into(),
+        out: None,
+        season: None,
+    };
+    let mut it = std::env::args().skip(1);
+    while let Some(arg) = it.next() {
+        let mut value = || it.next().ok_or_else(|| format!("{arg} needs a value"));
+        match arg.as_str() {
+            "--store" => args.store = value()?,
+            "--log" => args.log = Some(value()?),
+            "--snapshot" => args.snapshot = Some(value()?.into()),
+            "--format" => args.format = value()?,
+            "--out" => args.out = Some(value()?.into()),
+            "--season" => args.season = Some(value()?),
+            "-h" | "--help" => return Err(String::new()),
+            cmd if !cmd.starts_with('-') && args.command.is_empty() => args.command = cmd.into(),
+            other => return Err(format!("unexpected argument '{other}'")),
+        }
+    }
+    if args.command.is_empty() {
+        return Err("missing command".into());
+    }
+    Ok(args)
+}
+
+fn main() -> ExitCode {
+    dotenvy::dotenv().ok();
+    let args = match parse_args() {
+        Ok(a) => a,
+        Err(msg) => {
+            if !msg.is_empty() {
+                eprintln!("error: {msg}");
+            }
+            eprintln!("{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let store = match store::open_read_only(&args.store, args.log.clone()) {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("error: cannot open {} log: {e}", args.store);
+            return ExitCode::from(2);
+        }
+    };
+
+    let res = match args.command.as_str() {
+        "verify" => verify(store.as_ref()),
+        "replay" => replay(store.as_ref()),
+        "diff" => diff(store.as_ref(), args.snapshot.as_deref()),
+        "export" => export(store.as_ref(), &args.format, args.out.as_deref(), args.season.as_deref()),
+        other => {
+            eprintln!("error: unknown command '{other}'\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    match res {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(1),
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+type CmdResult = Result<bool, Box<dyn std::error::Error>>;
+
+fn verify(store: &dyn SubmissionStore) -> CmdResult {
+    let report = verify_chain(store)?;
+    let damage = store.damage();
+    println!("records: {}", report.records);
+    println!("head:    {}", report.head);
+    match &report.first_broken {
+        None => println!("chain:   intact"),
+        Some(b) => println!("chain:   BROKEN at record {}: {}", b.position, b.reason),
+    }
+    for d in &damage {
+        println!("damaged: {d}");
+    }
+    Ok(report.first_broken.is_none() && damage.is_empty())
+}
+
+/// Replays from `cursor` on top of whatever is in `sessions` and `guilds`
+/// and returns the wallet scores.
+fn replay_scores(
+    store: &dyn SubmissionStore,
+    sessions: &Sessions,
+    guilds: &Guilds,
+    cursor: u64,
+) -> Result<(ReplayReport, HashMap<PubKey, u32>), Box<dyn std::error::Error>> {
+    let rules = RulesHandle::from_env().map_err(|e| format!("scoring rules: {e}"))?;
+    let calendar = SeasonCalendar::from_env().map_err(|e| format!("seasons: {e}"))?;
+    let history = RulesHistory::open_read_only(&history_path())?;
+    let report = replay_into_sessions(store, sessions, guilds, &rules.current(), &history, &calendar, cursor)?;
+    let scores = sessions
+        .read()
+        .iter()
+        .map(|(k, s)| (k.clone(), s.get_score()))
+        .collect();
+    Ok((report, scores))
+}
+
+fn print_report(report: &ReplayReport) {
+    eprintln!(
+        "replayed {} records ({} skipped, {} rejected, {} reverted)",
+        report.replayed, report.skipped, report.rejected, report.reverted
+    );
+}
+
+fn replay(store: &dyn SubmissionStore) -> CmdResult {
+    let (report, scores) = replay_scores(store, &Sessions::new(), &Guilds::new(), 0)?;
+    print_report(&report);
+    let mut rows: Vec<(PubKey, u32)> = scores.into_iter().collect();
+    rows.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.as_bytes().cmp(b.0.as_bytes())));
+    for (wallet, score) in rows {
+        println!("{wallet}\t{score}");
+    }
+    Ok(true)
+}
+
+fn diff(store: &dyn SubmissionStore, file: Option<&std::path::Path>) -> CmdResult {
+    let snap = match file {
+        Some(path) => snapshot::read_snapshot(path)?,
+        None => {
+            let dir = snapshot::snapshot_dir();
+            snapshot::load_latest(&dir, store.cursor()?)?
+                .ok_or_else(|| format!("no usable snapshot in {}", dir.display()))?
+        }
+    };
+    let cursor = snap.cursor;
+
+    let (full_sessions, full_guilds) = (Sessions::new(), Guilds::new());
+    let (_, full) = replay_scores(store, &full_sessions, &full_guilds, 0)?;
+    let (resumed_sessions, resumed_guilds) = (Sessions::new(), Guilds::new());
+    snapshot::restore(&resumed_sessions, &resumed_guilds, snap);
+    let (tail, resumed) = replay_scores(store, &resumed_sessions, &resumed_guilds, cursor)?;
+    eprintln!("snapshot cursor {cursor}, {} records after it", tail.replayed + tail.skipped + tail.rejected + tail.reverted);
+
+    // BTreeMap on the hex key keeps the output stable
+    let mut wallets: BTreeMap<String, (Option<u32>, Option<u32>)> = BTreeMap::new();
+    for (k, v) in &full {
+        wallets.entry(k.to_string()).or_default().0 = Some(*v);
+    }
+    for (k, v) in &resumed {
+        wallets.entry(k.to_string()).or_default().1 = Some(*v);
+    }
+    let mut guilds: BTreeMap<String, (Option<u64>, Option<u64>)> = BTreeMap::new();
+    for (name, g) in full_guilds.read().iter() {
+        guilds.entry(name.clone()).or_default().0 = Some(g.score());
+    }
+    for (name, g) in resumed_guilds.read().iter() {
+        guilds.entry(name.clone()).or_default().1 = Some(g.score());
+    }
+
+    let show = |v: Option<u64>| v.map_or("-".to_string(), |v| v.to_string());
+    let mut mismatches = 0;
+    for (wallet, (replayed, restored)) in &wallets {
+        if replayed != restored {
+            mismatches += 1;
+            let (replayed, restored) = (replayed.map(u64::from), restored.map(u64::from));
+            println!("{wallet}\treplay={}\tsnapshot+tail={}", show(replayed), show(restored));
+        }
+    }
+    for (name, (replayed, restored)) in &guilds {
+        if replayed != restored {
+            mismatches += 1;
+            println!("guild:{name}\treplay={}\tsnapshot+tail={}", show(*replayed), show(*restored));
+        }
+    }
+    // the state roots also cover what scores do not show (bonus state, stats)
+    let full_root = hex::encode(state_root(&session_roots(full_sessions.read().iter())));
+    let resumed_root = hex::encode(state_root(&session_roots(resumed_sessions.read().iter())));
+    if full_root != resumed_root {
+        mismatches += 1;
+        println!("state root\treplay={full_root}\tsnapshot+tail={resumed_root}");
+    }
+    println!("{} wallets and {} guilds compared, {mismatches} differ", wallets.len(), guilds.len());
+    Ok(mismatches == 0)
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+fn write_csv(out: &mut dyn Write, entries: &[LeaderboardEntry]) -> io::Result<()> {
+    writeln!(out, "rank,wallet_tag,score,tier,mu_level,block_height,date_mined")?;
+    for e in entries {
+        writeln!(
+            out,
+            "{},{},{},{},{},{},{}",
+            e.rank,
+            csv_field(&e.wallet_tag),
+            e.score,
+            csv_field(&e.tier),
+            e.mu_level,
+            e.block_height,
+            e.date_mined.map(|d| d.to_rfc3339()).unwrap_or_default(),
+        )?;
+    }
+    Ok(())
+}
+
+fn export(
+    store: &dyn SubmissionStore,
+    format: &str,
+    out: Option<&std::path::Path>,
+    season: Option<&str>,
+) -> CmdResult {
+    if !matches!(format, "csv" | "json") {
+        return Err(format!("unknown format '{format}' (csv or json)").into());
+    }
+    let calendar = SeasonCalendar::from_env().map_err(|e| format!("seasons: {e}"))?;
+    let season = match season {
+        Some(id) => Some(calendar.get(id).ok_or_else(|| format!("unknown season '{id}'"))?),
+        None => None,
+    };
+    let sessions = Sessions::new();
+    let (report, _) = replay_scores(store, &sessions, &Guilds::new(), 0)?;
+    print_report(&report);
+    let tiers = TierTable::from_env().map_err(|e| format!("TIER_THRESHOLDS: {e}"))?;
+    let ranking = season.map_or(Ranking::AllTime, Ranking::Season);
+    let entries = build_leaderboard(&sessions, &tiers, ranking);
+
+    let mut out: Box<dyn Write> = match out {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(io::stdout().lock()),
+    };
+    if format == "csv" {
+        write_csv(out.as_mut(), &entries)?;
+    } else {
+        serde_json::to_writer_pretty(&mut out, &entries)?;
+        writeln!(out)?;
+    }
+    out.flush()?;
+    Ok(true)
+}
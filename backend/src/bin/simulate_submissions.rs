@@ -17,7 +17,7 @@ struct Submission {
 }
 
 fn generate_wallets(n: usize) -> Vec<String> {
-    let samples = vec![
+    let samples = [
         "kaspa:qz6wqg...8upg".to_string(),
         "kaspa:qq53sd...v83z".to_string(),
         "kaspa:sdmsdf...0qq3".to_string(),
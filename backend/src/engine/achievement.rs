@@ -0,0 +1,215 @@
+// backend/src/engine/achievement.rs
+
+use std::{collections::BTreeMap, path::Path};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::{Deserialize, Serialize};
+
+/// What unlocks an achievement.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Condition {
+    /// `count` mined superblocks at μ ≥ `min_mu`.
+    Mined {
+        #[serde(default)]
+        min_mu: u8,
+        #[serde(default = "one")]
+        count: u32,
+    },
+    /// `count` witnessed superblocks at μ ≥ `min_mu`.
+    Witnessed {
+        #[serde(default)]
+        min_mu: u8,
+        #[serde(default = "one")]
+        count: u32,
+    },
+    /// A season finished at `max_rank` or better; checked when it is archived.
+    SeasonRank { max_rank: usize },
+}
+
+fn one() -> u32 {
+    1
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Achievement {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub when: Condition,
+}
+
+/// Scored events per μ-level, the input to the event conditions.
+#[derive(Debug, Default, Clone, BorshSerialize, BorshDeserialize)]
+pub struct Stats {
+    mined: BTreeMap<u8, u32>,
+    witnessed: BTreeMap<u8, u32>,
+}
+
+impl Stats {
+    pub fn record(&mut self, mu_level: u8, is_witness: bool) {
+        *self.table(is_witness).entry(mu_level).or_default() += 1;
+    }
+
+    pub fn unrecord(&mut self, mu_level: u8, is_witness: bool) {
+        if let Some(n) = self.table(is_witness).get_mut(&mu_level) {
+            *n = n.saturating_sub(1);
+        }
+    }
+
+    /// Events of one type at μ ≥ `min_mu`.
+    pub fn count(&self, min_mu: u8, is_witness: bool) -> u32 {
+        let table = if is_witness { &self.witnessed } else { &self.mined };
+        table.range(min_mu..).map(|(_, n)| n).sum()
+    }
+
+    fn table(&mut self, is_witness: bool) -> &mut BTreeMap<u8, u32> {
+        if is_witness { &mut self.witnessed } else { &mut self.mined }
+    }
+}
+
+/// The configured achievements. Sessions keep the unlocked ids; the log has
+/// one `achievement` record per unlock and is what replay restores them from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AchievementBook {
+    achievements: Vec<Achievement>,
+}
+
+impl Default for AchievementBook {
+    fn default() -> Self {
+        let a = |id: &str, name: &str, description: &str, when| Achievement {
+            id: id.into(),
+            name: name.into(),
+            description: description.into(),
+            when,
+        };
+        Self {
+            achievements: vec![
+                a("first_mu18", "Deep Strike", "Mine a μ≥18 superblock", Condition::Mined { min_mu: 18, count: 1 }),
+                a("ten_witnesses", "Watchful Eye", "Witness 10 superblocks", Condition::Witnessed { min_mu: 0, count: 10 }),
+                a("honorius", "Honorius", "Mine a μ≥21 superblock", Condition::Mined { min_mu: 21, count: 1 }),
+                a("season_podium", "Podium", "Finish a season in the top 3", Condition::SeasonRank { max_rank: 3 }),
+            ],
+        }
+    }
+}
+
+impl AchievementBook {
+    /// `ACHIEVEMENTS_PATH` (TOML or JSON, an `achievements` list); unset
+    /// means the built-in set.
+    pub fn from_env() -> Result<Self, String> {
+        match std::env::var("ACHIEVEMENTS_PATH") {
+            Ok(p) if !p.trim().is_empty() => Self::load(Path::new(&p)),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        let book: Self = if path.extension().is_some_and(|e| e == "toml") {
+            toml::from_str(&text).map_err(|e| format!("{}: {e}", path.display()))?
+        } else {
+            serde_json::from_str(&text).map_err(|e| format!("{}: {e}", path.display()))?
+        };
+        book.validate()?;
+        Ok(book)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        for (i, a) in self.achievements.iter().enumerate() {
+            if a.id.trim().is_empty() {
+                return Err("an achievement has an empty id".into());
+            }
+            if self.achievements[..i].iter().any(|o| o.id == a.id) {
+                return Err(format!("achievement id '{}' is used twice", a.id));
+            }
+            match a.when {
+                Condition::Mined { count: 0, .. } | Condition::Witnessed { count: 0, .. } => {
+                    return Err(format!("achievement '{}' needs a count of at least 1", a.id));
+                }
+                Condition::SeasonRank { max_rank: 0 } => {
+                    return Err(format!("achievement '{}' needs a max_rank of at least 1", a.id));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    pub fn achievements(&self) -> &[Achievement] {
+        &self.achievements
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Achievement> {
+        self.achievements.iter().find(|a| a.id == id)
+    }
+
+    /// Event achievements `stats` meets that `is_unlocked` says are still locked.
+    pub fn newly_met(&self, stats: &Stats, is_unlocked: impl Fn(&str) -> bool) -> Vec<&Achievement> {
+        self.achievements
+            .iter()
+            .filter(|a| match a.when {
+                Condition::Mined { min_mu, count } => stats.count(min_mu, false) >= count,
+                Condition::Witnessed { min_mu, count } => stats.count(min_mu, true) >= count,
+                Condition::SeasonRank { .. } => false,
+            })
+            .filter(|a| !is_unlocked(&a.id))
+            .collect()
+    }
+
+    /// Season achievements a finish at `rank` earns.
+    pub fn for_season_rank(&self, rank: usize) -> impl Iterator<Item = &Achievement> {
+        self.achievements
+            .iter()
+            .filter(move |a| matches!(a.when, Condition::SeasonRank { max_rank } if rank <= max_rank))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(found: Vec<&Achievement>) -> Vec<&str> {
+        found.into_iter().map(|a| a.id.as_str()).collect()
+    }
+
+    #[test]
+    fn newly_met_checks_min_mu_and_count() {
+        let book = AchievementBook::default();
+        let mut stats = Stats::default();
+        let none_unlocked = |_: &str| false;
+
+        stats.record(17, false);
+        assert!(book.newly_met(&stats, none_unlocked).is_empty());
+        stats.record(18, false);
+        assert_eq!(ids(book.newly_met(&stats, none_unlocked)), ["first_mu18"]);
+        stats.record(21, false);
+        assert_eq!(ids(book.newly_met(&stats, none_unlocked)), ["first_mu18", "honorius"]);
+
+        for _ in 0..9 {
+            stats.record(15, true);
+        }
+        assert!(!ids(book.newly_met(&stats, none_unlocked)).contains(&"ten_witnesses"));
+        stats.record(15, true);
+        assert!(ids(book.newly_met(&stats, none_unlocked)).contains(&"ten_witnesses"));
+
+        // reorgs take events back out of the count
+        stats.unrecord(18, false);
+        stats.unrecord(21, false);
+        assert_eq!(ids(book.newly_met(&stats, none_unlocked)), ["ten_witnesses"]);
+    }
+
+    #[test]
+    fn newly_met_skips_unlocked_and_season_achievements() {
+        let book = AchievementBook::default();
+        let mut stats = Stats::default();
+        stats.record(21, false);
+        assert_eq!(ids(book.newly_met(&stats, |id| id == "first_mu18")), ["honorius"]);
+
+        assert_eq!(book.for_season_rank(3).map(|a| a.id.as_str()).collect::<Vec<_>>(), ["season_podium"]);
+        assert_eq!(book.for_season_rank(4).count(), 0);
+    }
+}
@@ -1,54 +1,257 @@
 // backend/src/engine/game.rs
 
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::{Deserialize, Serialize};
+
+use crate::engine::kdapp::CommandError;
+use crate::engine::rules::{ComboRule, StreakRule};
 use crate::episode::PayloadMetadata;
 use crate::state::pki::PubKey;
 
-#[derive(Default, Clone, Debug)]
+#[derive(Default, Clone, Debug, BorshSerialize, BorshDeserialize)]
 pub struct Game {
     pub score: u32,
+    pub bonus_state: BonusState,
+}
+
+/// What the bonus rules remember between commands. Tracked even while no
+/// bonus rule is configured, so enabling one later sees the history.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct BonusState {
+    /// Start (unix seconds) of the window the last mined block fell in,
+    /// and how many consecutive windows had one. Windows are measured in
+    /// the streak rule's `window_hours` at the time of the block.
+    pub streak_window: Option<u64>,
+    pub streak_len: u32,
+    /// When the last mined block was accepted, until a witness uses it.
+    pub combo_open: Option<u64>,
+}
+
+/// One bonus line item, next to the base points of an event.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Bonus {
+    /// Mined superblocks in `windows` consecutive streak windows.
+    Streak { windows: u32, points: u32 },
+    /// A witness soon after the wallet's own mined superblock.
+    Combo { points: u32 },
+}
+
+impl Bonus {
+    pub fn points(&self) -> u32 {
+        match self {
+            Self::Streak { points, .. } | Self::Combo { points } => *points,
+        }
+    }
 }
 
+/// The bonus rules in force; `None` disables a bonus.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BonusRules {
+    pub streak: Option<StreakRule>,
+    pub combo: Option<ComboRule>,
+}
+
+/// Points are resolved by the caller from the `ScoringRules` in force.
+/// `logged` replays the bonus line items a record already holds: the bonus
+/// state still follows `rules`, but exactly those items are awarded.
 #[derive(Debug, Clone)]
 pub enum GameCommand {
-    AddPoints { level: u8 },
-    WitnessPoints { level: u8 },
+    AddPoints { points: u32, rules: BonusRules, logged: Option<Vec<Bonus>> },
+    WitnessPoints { points: u32, rules: BonusRules, logged: Option<Vec<Bonus>> },
+}
+
+/// What one command earned: base points plus bonus line items.
+#[derive(Debug, Clone, Default)]
+pub struct Award {
+    pub base: u32,
+    pub bonuses: Vec<Bonus>,
+}
+
+impl Award {
+    pub fn total(&self) -> u32 {
+        self.base + self.bonuses.iter().map(Bonus::points).sum::<u32>()
+    }
 }
 
 impl Game {
+    /// Refuses, changing nothing, if the award would overflow the score.
     pub fn execute(
         &mut self,
         cmd: &GameCommand,
         _auth: Option<PubKey>,
-        _metadata: &PayloadMetadata,
-    ) -> u32 {
-        let delta = match cmd {
-            GameCommand::AddPoints { level } => Self::points_for_level(*level),
-            GameCommand::WitnessPoints { level } => Self::points_for_level(*level) / 2,
+        metadata: &PayloadMetadata,
+    ) -> Result<Award, CommandError> {
+        let now = metadata.accepting_time;
+        let (base, rules, logged, is_witness) = match cmd {
+            GameCommand::AddPoints { points, rules, logged } => (*points, rules, logged, false),
+            GameCommand::WitnessPoints { points, rules, logged } => (*points, rules, logged, true),
         };
-        self.score += delta;
-        delta
-    }
 
-    pub fn rollback(&mut self, rollback: u32) -> bool {
-        if self.score >= rollback {
-            self.score -= rollback;
-            true
+        let mut earned = Vec::new();
+        let mut state = self.bonus_state;
+        if is_witness {
+            if let Some(rule) = rules.combo {
+                if state.combo_open.is_some_and(|t| now.saturating_sub(t) <= rule.within_secs()) {
+                    earned.push(Bonus::Combo { points: rule.points });
+                    state.combo_open = None;
+                }
+            }
         } else {
-            false
+            let window_secs = rules.streak.map_or(86_400, |r| r.window_secs());
+            let window = now - now % window_secs;
+            match state.streak_window {
+                Some(w) if w == window => {}
+                Some(w) if w + window_secs == window => {
+                    state.streak_len += 1;
+                    if let Some(points) = rules.streak.and_then(|r| r.points(state.streak_len)) {
+                        earned.push(Bonus::Streak { windows: state.streak_len, points });
+                    }
+                }
+                _ => state.streak_len = 1,
+            }
+            state.streak_window = Some(window);
+            state.combo_open = Some(now);
+        }
+
+        let award = Award { base, bonuses: logged.clone().unwrap_or(earned) };
+        let score = award
+            .bonuses
+            .iter()
+            .try_fold(award.base, |total, b| total.checked_add(b.points()))
+            .and_then(|total| self.score.checked_add(total))
+            .ok_or(CommandError::ScoreOverflow)?;
+        self.score = score;
+        self.bonus_state = state;
+        Ok(award)
+    }
+
+    /// Takes back `points` and restores the bonus state from before the
+    /// command. Reverts run newest first, so the restored state is the one
+    /// that preceded the reverted events. Leaves the game untouched and
+    /// returns false if the score cannot cover `points`.
+    pub fn rollback(&mut self, points: u32, prev: BonusState) -> bool {
+        if self.score < points {
+            return false;
         }
+        self.score -= points;
+        self.bonus_state = prev;
+        true
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: u64 = 3600;
+    /// Noon on 2025-01-01, inside an hour window and a day window.
+    const T0: u64 = 1_735_732_800;
 
-    /// Scoring table — higher μ gives more points (no tiers/emojis)
-    fn points_for_level(mu: u8) -> u32 {
-        match mu {
-            15 => 15,
-            16 => 25,
-            17 => 40,
-            18 => 70,
-            19 => 120,
-            20 => 200,
-            21..=u8::MAX => 400,
-            _ => 0,
+    fn bonus_rules() -> BonusRules {
+        BonusRules {
+            streak: Some(StreakRule { window_hours: 1, points_per_window: 10, max_points: 25 }),
+            combo: Some(ComboRule { within_minutes: 30, points: 5 }),
         }
     }
+
+    fn mine(game: &mut Game, at: u64) -> Award {
+        let cmd = GameCommand::AddPoints { points: 100, rules: bonus_rules(), logged: None };
+        game.execute(&cmd, None, &PayloadMetadata { accepting_time: at }).unwrap()
+    }
+
+    fn witness(game: &mut Game, at: u64) -> Award {
+        let cmd = GameCommand::WitnessPoints { points: 50, rules: bonus_rules(), logged: None };
+        game.execute(&cmd, None, &PayloadMetadata { accepting_time: at }).unwrap()
+    }
+
+    #[test]
+    fn streak_grows_in_the_next_window_and_resets_after_a_gap() {
+        let mut game = Game::default();
+        assert!(mine(&mut game, T0).bonuses.is_empty());
+        // same window: no bonus, no growth
+        assert!(mine(&mut game, T0 + 600).bonuses.is_empty());
+        assert_eq!(game.bonus_state.streak_len, 1);
+
+        let next = mine(&mut game, T0 + HOUR);
+        assert_eq!(next.bonuses, vec![Bonus::Streak { windows: 2, points: 10 }]);
+        assert_eq!(next.total(), 110);
+
+        // an empty window in between starts over
+        assert!(mine(&mut game, T0 + 3 * HOUR).bonuses.is_empty());
+        assert_eq!(game.bonus_state.streak_len, 1);
+        assert_eq!(game.score, 4 * 100 + 10);
+    }
+
+    #[test]
+    fn streak_bonus_stops_at_max_points() {
+        let mut game = Game::default();
+        let awards: Vec<Award> = (0..5).map(|w| mine(&mut game, T0 + w * HOUR)).collect();
+        let bonuses: Vec<u32> = awards.iter().map(|a| a.total() - a.base).collect();
+        assert_eq!(bonuses, vec![0, 10, 20, 25, 25]);
+        assert_eq!(game.bonus_state.streak_len, 5);
+    }
+
+    #[test]
+    fn combo_pays_once_per_mined_block_within_the_limit() {
+        let mut game = Game::default();
+        mine(&mut game, T0);
+        assert_eq!(witness(&mut game, T0 + 30 * 60).bonuses, vec![Bonus::Combo { points: 5 }]);
+        // the mined block was used up by the first witness
+        assert!(witness(&mut game, T0 + 31 * 60).bonuses.is_empty());
+
+        mine(&mut game, T0 + HOUR);
+        assert!(witness(&mut game, T0 + HOUR + 31 * 60).bonuses.is_empty());
+        // the second mined block also extended the streak
+        assert_eq!(game.score, 2 * 100 + 10 + 3 * 50 + 5);
+    }
+
+    #[test]
+    fn logged_replay_reproduces_the_same_awards() {
+        let mut live = Game::default();
+        let times = [T0, T0 + 60, T0 + HOUR, T0 + HOUR + 120, T0 + 2 * HOUR];
+        let awards: Vec<Award> = times
+            .iter()
+            .enumerate()
+            .map(|(i, &at)| if i % 2 == 1 { witness(&mut live, at) } else { mine(&mut live, at) })
+            .collect();
+
+        let mut replayed = Game::default();
+        for (i, (&at, award)) in times.iter().zip(&awards).enumerate() {
+            let logged = Some(award.bonuses.clone());
+            let cmd = if i % 2 == 1 {
+                GameCommand::WitnessPoints { points: award.base, rules: bonus_rules(), logged }
+            } else {
+                GameCommand::AddPoints { points: award.base, rules: bonus_rules(), logged }
+            };
+            let again = replayed.execute(&cmd, None, &PayloadMetadata { accepting_time: at }).unwrap();
+            assert_eq!((again.base, &again.bonuses), (award.base, &award.bonuses));
+        }
+        assert_eq!(replayed.score, live.score);
+        assert_eq!(replayed.bonus_state, live.bonus_state);
+    }
+
+    #[test]
+    fn award_past_u32_max_is_refused_without_changes() {
+        let mut game = Game { score: u32::MAX - 50, bonus_state: BonusState::default() };
+        let cmd = GameCommand::AddPoints { points: 100, rules: bonus_rules(), logged: None };
+        let err = game.execute(&cmd, None, &PayloadMetadata { accepting_time: T0 }).unwrap_err();
+        assert_eq!(err, CommandError::ScoreOverflow);
+        assert_eq!(game.score, u32::MAX - 50);
+        assert_eq!(game.bonus_state, BonusState::default());
+    }
+
+    #[test]
+    fn rollback_past_zero_leaves_the_game_untouched() {
+        let state = BonusState { streak_window: Some(3600), streak_len: 3, combo_open: Some(4000) };
+        let mut game = Game { score: 10, bonus_state: state };
+
+        assert!(!game.rollback(20, BonusState::default()));
+        assert_eq!(game.score, 10);
+        assert_eq!(game.bonus_state, state);
+
+        assert!(game.rollback(10, BonusState::default()));
+        assert_eq!(game.score, 0);
+        assert_eq!(game.bonus_state, BonusState::default());
+    }
 }
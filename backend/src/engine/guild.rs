@@ -0,0 +1,233 @@
+// backend/src/engine/guild.rs
+
+use std::fmt;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use crate::episode::{Episode, EpisodeError, PayloadMetadata};
+use crate::state::pki::PubKey;
+
+/// A named group of wallets. The members are the episode's participants;
+/// every point a member scores while in the guild is also credited here.
+#[derive(Default, Clone, BorshSerialize, BorshDeserialize)]
+pub struct GuildSession {
+    members: Vec<PubKey>,
+    score: u64,
+    /// Unix seconds the guild was founded.
+    founded_at: u64,
+}
+
+/// Why a guild refused a command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GuildError {
+    /// Join for a wallet that is already in this guild.
+    AlreadyMember,
+    /// Leave (or credit) for a wallet that is not in this guild.
+    NotAMember,
+    /// Unsigned, or signed by a key other than the member it is about.
+    UnauthorizedSigner,
+    /// `Found` on a guild that already exists.
+    AlreadyFounded,
+}
+
+impl fmt::Display for GuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AlreadyMember => write!(f, "wallet is already a member of this guild"),
+            Self::NotAMember => write!(f, "wallet is not a member of this guild"),
+            Self::UnauthorizedSigner => write!(f, "signer is not allowed to act for this wallet"),
+            Self::AlreadyFounded => write!(f, "guild already exists"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum GuildCommand {
+    /// First member of a new guild; the manager initializes the episode
+    /// with the founder as its only participant.
+    Found { member: PubKey },
+    Join { member: PubKey },
+    Leave { member: PubKey },
+    /// Points a member just scored.
+    Credit { member: PubKey, points: u32 },
+}
+
+/// Undo information for one guild command.
+#[derive(Debug, Clone)]
+pub enum GuildRollback {
+    Found,
+    Join { member: PubKey },
+    Leave { member: PubKey, index: usize },
+    Credit { points: u32 },
+}
+
+impl GuildRollback {
+    /// Undo for a credit whose event was reverted after the fact (reorg).
+    pub fn credit(points: u32) -> Self {
+        Self::Credit { points }
+    }
+}
+
+impl Episode for GuildSession {
+    type Command = GuildCommand;
+    type CommandError = GuildError;
+    type CommandRollback = GuildRollback;
+
+    fn initialize(participants: Vec<PubKey>, metadata: &PayloadMetadata) -> Self {
+        Self { members: participants, score: 0, founded_at: metadata.accepting_time }
+    }
+
+    fn execute(
+        &mut self,
+        cmd: &Self::Command,
+        auth: Option<PubKey>,
+        _metadata: &PayloadMetadata,
+    ) -> Result<Self::CommandRollback, EpisodeError<Self::CommandError>> {
+        let refuse = |e| Err(EpisodeError::CommandError(e));
+        let member = match cmd {
+            GuildCommand::Found { member }
+            | GuildCommand::Join { member }
+            | GuildCommand::Leave { member }
+            | GuildCommand::Credit { member, .. } => member,
+        };
+        if auth.as_ref() != Some(member) {
+            return refuse(GuildError::UnauthorizedSigner);
+        }
+        let index = self.members.iter().position(|m| m == member);
+
+        match cmd {
+            // a fresh episode holds exactly its founder and nothing else
+            GuildCommand::Found { .. } => {
+                if self.members.len() != 1 || index.is_none() || self.score != 0 {
+                    return refuse(GuildError::AlreadyFounded);
+                }
+                Ok(GuildRollback::Found)
+            }
+            GuildCommand::Join { member } => {
+                if index.is_some() {
+                    return refuse(GuildError::AlreadyMember);
+                }
+                self.members.push(member.clone());
+                Ok(GuildRollback::Join { member: member.clone() })
+            }
+            GuildCommand::Leave { member } => {
+                let Some(index) = index else { return refuse(GuildError::NotAMember) };
+                self.members.remove(index);
+                Ok(GuildRollback::Leave { member: member.clone(), index })
+            }
+            GuildCommand::Credit { points, .. } => {
+                if index.is_none() {
+                    return refuse(GuildError::NotAMember);
+                }
+                self.score += *points as u64;
+                Ok(GuildRollback::Credit { points: *points })
+            }
+        }
+    }
+
+    fn rollback(&mut self, rollback: GuildRollback) -> bool {
+        match rollback {
+            GuildRollback::Found => true,
+            GuildRollback::Join { member } => {
+                let before = self.members.len();
+                self.members.retain(|m| m != &member);
+                self.members.len() < before
+            }
+            GuildRollback::Leave { member, index } => {
+                self.members.insert(index.min(self.members.len()), member);
+                true
+            }
+            GuildRollback::Credit { points } => match self.score.checked_sub(points as u64) {
+                Some(score) => {
+                    self.score = score;
+                    true
+                }
+                None => false,
+            },
+        }
+    }
+}
+
+impl GuildSession {
+    pub fn members(&self) -> &[PubKey] {
+        &self.members
+    }
+
+    pub fn is_member(&self, wallet: &PubKey) -> bool {
+        self.members.contains(wallet)
+    }
+
+    pub fn score(&self) -> u64 {
+        self.score
+    }
+
+    pub fn founded_at(&self) -> u64 {
+        self.founded_at
+    }
+}
+
+/// Guild names: 1-32 letters, digits, `-` or `_`. The name is the key.
+pub fn valid_guild_name(name: &str) -> bool {
+    (1..=32).contains(&name.len())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const META: PayloadMetadata = PayloadMetadata { accepting_time: 1_000 };
+
+    fn run(guild: &mut GuildSession, cmd: GuildCommand, signer: &PubKey) -> Result<GuildRollback, GuildError> {
+        guild.execute(&cmd, Some(signer.clone()), &META).map_err(|e| match e {
+            EpisodeError::CommandError(e) => e,
+            EpisodeError::InternalError(msg) => panic!("{msg}"),
+        })
+    }
+
+    #[test]
+    fn members_found_join_and_leave() {
+        let (a, b) = (PubKey::new([1; 32]), PubKey::new([2; 32]));
+        let mut guild = GuildSession::initialize(vec![a.clone()], &META);
+        run(&mut guild, GuildCommand::Found { member: a.clone() }, &a).unwrap();
+        assert_eq!(guild.founded_at(), 1_000);
+
+        run(&mut guild, GuildCommand::Join { member: b.clone() }, &b).unwrap();
+        assert_eq!(guild.members(), [a.clone(), b.clone()]);
+        let again = run(&mut guild, GuildCommand::Join { member: b.clone() }, &b);
+        assert_eq!(again.unwrap_err(), GuildError::AlreadyMember);
+        let refound = run(&mut guild, GuildCommand::Found { member: a.clone() }, &a);
+        assert_eq!(refound.unwrap_err(), GuildError::AlreadyFounded);
+
+        let left = run(&mut guild, GuildCommand::Leave { member: a.clone() }, &a).unwrap();
+        assert_eq!(guild.members(), std::slice::from_ref(&b));
+        let again = run(&mut guild, GuildCommand::Leave { member: a.clone() }, &a);
+        assert_eq!(again.unwrap_err(), GuildError::NotAMember);
+        // undoing the leave puts the member back in place
+        assert!(guild.rollback(left));
+        assert_eq!(guild.members(), [a, b]);
+    }
+
+    #[test]
+    fn only_members_earn_credit_and_only_for_themselves() {
+        let (a, b) = (PubKey::new([1; 32]), PubKey::new([2; 32]));
+        let mut guild = GuildSession::initialize(vec![a.clone()], &META);
+        run(&mut guild, GuildCommand::Credit { member: a.clone(), points: 70 }, &a).unwrap();
+        assert_eq!(
+            run(&mut guild, GuildCommand::Credit { member: b.clone(), points: 5 }, &b).unwrap_err(),
+            GuildError::NotAMember
+        );
+        assert_eq!(
+            run(&mut guild, GuildCommand::Join { member: b.clone() }, &a).unwrap_err(),
+            GuildError::UnauthorizedSigner
+        );
+
+        // credit stays with the guild after the member leaves
+        run(&mut guild, GuildCommand::Leave { member: a.clone() }, &a).unwrap();
+        assert_eq!(guild.score(), 70);
+        // a reorg can take it back, but never below zero
+        assert!(!guild.rollback(GuildRollback::credit(71)));
+        assert!(guild.rollback(GuildRollback::credit(70)));
+        assert_eq!(guild.score(), 0);
+    }
+}
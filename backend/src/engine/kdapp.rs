@@ -1,52 +1,457 @@
 // backend/src/engine/kdapp.rs
 
+use std::{collections::BTreeMap, fmt, sync::Arc};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
 use crate::episode::{Episode, EpisodeError, PayloadMetadata};
-use crate::engine::game::{Game, GameCommand};
+use crate::engine::achievement::Stats;
+use crate::engine::game::{Bonus, BonusRules, BonusState, Game, GameCommand};
+use crate::engine::rules::ScoringRules;
+use crate::engine::tier::{Tier, TierTable};
 use crate::state::pki::PubKey;
 use crate::state::types::SuperblockEvent;
 
-#[derive(Default, Clone)]
+#[derive(Default, Clone, BorshSerialize, BorshDeserialize)]
 pub struct MueHeroSession {
     game: Game,
+    /// Keys allowed to sign commands for this session (the wallet itself).
+    participants: Vec<PubKey>,
+    /// What each `(block_height, is_witness)` earned: the dedup index and
+    /// the rollback record used when a reorg orphans the block. Height 0
+    /// means "unknown" (older witness reports) and is never tracked.
+    scored: BTreeMap<(u64, bool), Scored>,
+    /// Points per season id, next to the all-time score in `game`.
+    seasons: BTreeMap<String, u32>,
+    /// Points per UTC day of `accepting_time`, for the decayed view. Kept
+    /// per day rather than as one decayed total so the half-life can change.
+    daily: BTreeMap<u64, u32>,
+    /// Scored events per μ-level, for achievement conditions.
+    stats: Stats,
+    /// Unlocked achievement ids and when (unix seconds).
+    achievements: BTreeMap<String, u64>,
+    /// The latest scored event, all-time and per season id, for the
+    /// leaderboard's metadata columns.
+    last_event: Option<LastEvent>,
+    season_last_event: BTreeMap<String, LastEvent>,
+}
+
+/// What the leaderboard shows about a wallet's latest scored event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct LastEvent {
+    pub mu_level: u8,
+    pub block_height: u64,
+    /// `accepting_time`, unix seconds.
+    pub at: u64,
+}
+
+const SECS_PER_DAY: u64 = 86_400;
+
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
+struct Scored {
+    points: u32,
+    season: Option<String>,
+    day: u64,
+    prev: BonusState,
+    mu_level: u8,
+    /// `accepting_time`, so a rollback can restore the latest event.
+    at: u64,
+}
+
+/// Why a session refused a command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommandError {
+    /// The block's μ-level is under the rules' `min_mu_level`.
+    BelowThreshold { mu_level: u8, min_mu_level: u8 },
+    /// This wallet already scored this block as this event type.
+    DuplicateBlock { block_height: u64 },
+    /// Unsigned, or signed by a key that may not act for this session.
+    /// The handlers sign with the body's wallet until submissions carry
+    /// signatures, so only direct engine callers see this for now.
+    UnauthorizedSigner,
+    /// A witness for a block nobody has recorded mining.
+    WitnessWithoutMinedBlock { block_height: u64 },
+    /// A witness for a block the same wallet mined.
+    SelfWitness { block_height: u64 },
+    /// The block already has `cap` witnesses.
+    WitnessCapReached { block_height: u64, cap: usize },
+    /// The season no longer accepts events.
+    SeasonClosed,
+    /// The points would take the score past `u32::MAX`.
+    ScoreOverflow,
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BelowThreshold { mu_level, min_mu_level } => {
+                write!(f, "μ-level {mu_level} is below the scoring threshold {min_mu_level}")
+            }
+            Self::DuplicateBlock { block_height } => {
+                write!(f, "block {block_height} already recorded for this wallet")
+            }
+            Self::UnauthorizedSigner => write!(f, "signer is not allowed to act for this wallet"),
+            Self::WitnessWithoutMinedBlock { block_height } => {
+                write!(f, "no mined block recorded at height {block_height}")
+            }
+            Self::SelfWitness { block_height } => {
+                write!(f, "a wallet cannot witness its own block {block_height}")
+            }
+            Self::WitnessCapReached { block_height, cap } => {
+                write!(f, "block {block_height} has reached its cap of {cap} witnesses")
+            }
+            Self::SeasonClosed => write!(f, "season is closed"),
+            Self::ScoreOverflow => write!(f, "the points would overflow the wallet's score"),
+        }
+    }
+}
+
+/// How a command's points are decided.
+#[derive(Debug, Clone)]
+pub enum Scoring {
+    /// Judge the event under these rules (live submissions, legacy records).
+    Rules(Arc<ScoringRules>),
+    /// Replay of a record the engine already scored: award exactly the
+    /// logged base points and bonus line items, whatever the rules say now.
+    /// `bonus_rules` only keeps the streak/combo state moving.
+    Logged { points: u32, bonuses: Vec<Bonus>, bonus_rules: BonusRules },
+}
+
+/// The season an event counts toward.
+#[derive(Debug, Clone)]
+pub struct SeasonRef {
+    pub id: String,
+    /// Final standings are archived; the season takes no more events.
+    pub closed: bool,
+}
+
+/// A superblock event and the scoring it is judged under.
+#[derive(Debug, Clone)]
+pub struct MueCommand {
+    pub event: SuperblockEvent,
+    pub scoring: Scoring,
+    /// `None` when no season was running at the event's time.
+    pub season: Option<SeasonRef>,
+}
+
+/// Undo information for one executed command, with what it earned.
+#[derive(Debug, Clone)]
+pub struct MueRollback {
+    /// Total awarded, bonuses included.
+    pub points: u32,
+    /// Bonus line items; `points` minus their sum is the base.
+    pub bonuses: Vec<Bonus>,
+    block: Option<(u64, bool)>,
+    season: Option<String>,
+    day: u64,
+    prev: BonusState,
+    mu_level: u8,
+    is_witness: bool,
+    at: u64,
 }
 
 impl Episode for MueHeroSession {
-    type Command = SuperblockEvent;
-    type CommandError = ();          // no custom command errors for now
-    type CommandRollback = u32;
+    type Command = MueCommand;
+    type CommandError = CommandError;
+    type CommandRollback = MueRollback;
 
-    fn initialize(_participants: Vec<PubKey>, _metadata: &PayloadMetadata) -> Self {
-        Self { game: Game::default() }
+    fn initialize(participants: Vec<PubKey>, _metadata: &PayloadMetadata) -> Self {
+        Self { participants, ..Self::default() }
     }
 
     fn execute(
         &mut self,
         cmd: &Self::Command,
-        _auth: Option<PubKey>,
-        _metadata: &PayloadMetadata,
+        auth: Option<PubKey>,
+        metadata: &PayloadMetadata,
     ) -> Result<Self::CommandRollback, EpisodeError<Self::CommandError>> {
-        let game_cmd = if cmd.is_witness {
-            GameCommand::WitnessPoints { level: cmd.mu_level }
+        if !auth.as_ref().is_some_and(|pk| self.participants.contains(pk)) {
+            return Err(EpisodeError::CommandError(CommandError::UnauthorizedSigner));
+        }
+        if cmd.season.as_ref().is_some_and(|s| s.closed) {
+            return Err(EpisodeError::CommandError(CommandError::SeasonClosed));
+        }
+        let event = &cmd.event;
+        let (points, bonus_rules, logged) = match &cmd.scoring {
+            Scoring::Rules(rules) => {
+                if event.mu_level < rules.min_mu_level {
+                    return Err(EpisodeError::CommandError(CommandError::BelowThreshold {
+                        mu_level: event.mu_level,
+                        min_mu_level: rules.min_mu_level,
+                    }));
+                }
+                (rules.points(event.mu_level, event.is_witness), rules.bonus_rules(), None)
+            }
+            Scoring::Logged { points, bonuses, bonus_rules } => {
+                (*points, *bonus_rules, Some(bonuses.clone()))
+            }
+        };
+        let block = (event.block_height != 0).then_some((event.block_height, event.is_witness));
+        if let Some(key) = block {
+            if self.scored.contains_key(&key) {
+                return Err(EpisodeError::CommandError(CommandError::DuplicateBlock {
+                    block_height: event.block_height,
+                }));
+            }
+        }
+
+        let game_cmd = if event.is_witness {
+            GameCommand::WitnessPoints { points, rules: bonus_rules, logged }
         } else {
-            GameCommand::AddPoints { level: cmd.mu_level }
+            GameCommand::AddPoints { points, rules: bonus_rules, logged }
         };
 
-        let delta = self.game.execute(&game_cmd, _auth, _metadata);
-        Ok(delta)
+        let prev = self.game.bonus_state;
+        let award = self.game.execute(&game_cmd, auth, metadata).map_err(EpisodeError::CommandError)?;
+        let points = award.total();
+        let season = cmd.season.as_ref().map(|s| s.id.clone());
+        if let Some(id) = &season {
+            *self.seasons.entry(id.clone()).or_default() += points;
+        }
+        let day = metadata.accepting_time / SECS_PER_DAY;
+        *self.daily.entry(day).or_default() += points;
+        self.stats.record(event.mu_level, event.is_witness);
+        let last = LastEvent { mu_level: event.mu_level, block_height: event.block_height, at: metadata.accepting_time };
+        if self.last_event.is_none_or(|l| last.at >= l.at) {
+            self.last_event = Some(last);
+        }
+        if let Some(id) = &season {
+            let entry = self.season_last_event.entry(id.clone()).or_insert(last);
+            if last.at >= entry.at {
+                *entry = last;
+            }
+        }
+        if let Some(key) = block {
+            let (mu_level, at) = (event.mu_level, metadata.accepting_time);
+            self.scored.insert(key, Scored { points, season: season.clone(), day, prev, mu_level, at });
+        }
+        Ok(MueRollback {
+            points,
+            bonuses: award.bonuses,
+            block,
+            season,
+            day,
+            prev,
+            mu_level: event.mu_level,
+            is_witness: event.is_witness,
+            at: metadata.accepting_time,
+        })
     }
 
-    fn rollback(&mut self, rollback: u32) -> bool {
-        self.game.rollback(rollback)
+    /// Refuses, changing nothing, if the score cannot cover the points.
+    fn rollback(&mut self, rollback: MueRollback) -> bool {
+        if !self.can_roll_back(&rollback) {
+            return false;
+        }
+        if let Some(key) = rollback.block {
+            self.scored.remove(&key);
+        }
+        if let Some(score) = rollback.season.as_ref().and_then(|id| self.seasons.get_mut(id)) {
+            *score = score.saturating_sub(rollback.points);
+        }
+        if let Some(score) = self.daily.get_mut(&rollback.day) {
+            *score = score.saturating_sub(rollback.points);
+        }
+        self.stats.unrecord(rollback.mu_level, rollback.is_witness);
+        let undone = rollback.block.map(|(block_height, _)| LastEvent {
+            mu_level: rollback.mu_level,
+            block_height,
+            at: rollback.at,
+        });
+        if undone.is_some() && self.last_event == undone {
+            self.last_event = self.latest_scored(None);
+        }
+        if let Some(id) = &rollback.season {
+            if undone.is_some() && self.season_last_event.get(id).copied() == undone {
+                match self.latest_scored(Some(id)) {
+                    Some(last) => { self.season_last_event.insert(id.clone(), last); }
+                    None => { self.season_last_event.remove(id); }
+                }
+            }
+        }
+        self.game.rollback(rollback.points, rollback.prev)
     }
 }
 
 impl MueHeroSession {
+    /// Undo record for the command that scored this block, if any.
+    pub fn rollback_for(&self, block_height: u64, is_witness: bool) -> Option<MueRollback> {
+        let key = (block_height, is_witness);
+        self.scored.get(&key).map(|s| MueRollback {
+            points: s.points,
+            bonuses: Vec::new(),
+            block: Some(key),
+            season: s.season.clone(),
+            day: s.day,
+            prev: s.prev,
+            mu_level: s.mu_level,
+            is_witness,
+            at: s.at,
+        })
+    }
+
+    /// Whether the score still covers what `rollback` takes back.
+    pub fn can_roll_back(&self, rollback: &MueRollback) -> bool {
+        self.game.score >= rollback.points
+    }
+
+    /// Latest remaining scored block, in `season` when given.
+    fn latest_scored(&self, season: Option<&str>) -> Option<LastEvent> {
+        self.scored
+            .iter()
+            .filter(|(_, s)| season.is_none_or(|id| s.season.as_deref() == Some(id)))
+            .max_by_key(|((block_height, _), s)| (s.at, *block_height))
+            .map(|(&(block_height, _), s)| LastEvent { mu_level: s.mu_level, block_height, at: s.at })
+    }
+
+    /// Whether this wallet has a live record for the block as this event type.
+    pub fn has_scored(&self, block_height: u64, is_witness: bool) -> bool {
+        self.scored.contains_key(&(block_height, is_witness))
+    }
+
     pub fn get_score(&self) -> u32 {
         self.game.score
     }
 
-    // Optional: keep for UI compatibility, tierless (just return score as text)
-    pub fn get_rank(&self) -> String {
-        self.game.score.to_string()
+    /// Score with every day's points halved per `half_life_days` of age at
+    /// `now` (unix seconds). Days are weighted from their midpoint.
+    pub fn decayed_score(&self, half_life_days: u32, now: u64) -> f64 {
+        let today = now as f64 / SECS_PER_DAY as f64;
+        self.daily
+            .iter()
+            .map(|(&day, &points)| {
+                let age = (today - (day as f64 + 0.5)).max(0.0);
+                points as f64 * 0.5f64.powf(age / half_life_days as f64)
+            })
+            .sum()
+    }
+
+    /// Points earned in one season; `None` if the wallet did not play it.
+    pub fn season_score(&self, season: &str) -> Option<u32> {
+        self.seasons.get(season).copied()
+    }
+
+    /// Latest scored event, in `season` when given.
+    pub fn last_event(&self, season: Option<&str>) -> Option<LastEvent> {
+        match season {
+            Some(id) => self.season_last_event.get(id).copied(),
+            None => self.last_event,
+        }
+    }
+
+    pub fn stats(&self) -> &Stats {
+        &self.stats
+    }
+
+    /// Unlocked achievement ids and when, in unix seconds.
+    pub fn achievements(&self) -> &BTreeMap<String, u64> {
+        &self.achievements
+    }
+
+    pub fn is_unlocked(&self, id: &str) -> bool {
+        self.achievements.contains_key(id)
+    }
+
+    /// Marks `id` unlocked at `at`; false if it already was.
+    pub fn unlock(&mut self, id: &str, at: u64) -> bool {
+        if self.is_unlocked(id) {
+            return false;
+        }
+        self.achievements.insert(id.to_string(), at);
+        true
+    }
+
+    /// Takes an unlock back (its record was reverted or never persisted).
+    pub fn relock(&mut self, id: &str) -> bool {
+        self.achievements.remove(id).is_some()
+    }
+
+    pub fn tier<'a>(&self, tiers: &'a TierTable) -> &'a Tier {
+        tiers.tier_for(self.game.score)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(wallet: &PubKey) -> MueHeroSession {
+        MueHeroSession::initialize(vec![wallet.clone()], &PayloadMetadata { accepting_time: 0 })
+    }
+
+    fn mine(session: &mut MueHeroSession, wallet: &PubKey, mu_level: u8, block_height: u64, at: u64) -> MueRollback {
+        let event = SuperblockEvent {
+            wallet: hex::encode(wallet.as_bytes()),
+            mu_level,
+            is_witness: false,
+            merkle_root: None,
+            proof: None,
+            witness_index: None,
+            block_height,
+        };
+        let cmd = MueCommand { event, scoring: Scoring::Rules(Arc::new(ScoringRules::default())), season: None };
+        session.execute(&cmd, Some(wallet.clone()), &PayloadMetadata { accepting_time: at }).unwrap()
+    }
+
+    #[test]
+    fn rollback_restores_the_previous_latest_event() {
+        let wallet = PubKey::new([1; 32]);
+        let mut s = session(&wallet);
+        mine(&mut s, &wallet, 18, 5, 1_000);
+        let rb = mine(&mut s, &wallet, 16, 8, 2_000);
+        assert_eq!(s.last_event(None).map(|l| l.block_height), Some(8));
+
+        assert!(s.rollback(rb));
+        assert_eq!(s.last_event(None), Some(LastEvent { mu_level: 18, block_height: 5, at: 1_000 }));
+        let rb = s.rollback_for(5, false).unwrap();
+        assert!(s.rollback(rb));
+        assert_eq!(s.last_event(None), None);
+    }
+
+    #[test]
+    fn refused_rollback_changes_nothing() {
+        let wallet = PubKey::new([1; 32]);
+        let mut s = session(&wallet);
+        let stale = mine(&mut s, &wallet, 18, 5, 1_000);
+        assert!(s.rollback(s.rollback_for(5, false).unwrap()));
+        mine(&mut s, &wallet, 16, 6, 2_000);
+
+        let before = s.try_to_vec().unwrap();
+        assert!(stale.points > s.get_score());
+        assert!(!s.rollback(stale));
+        assert_eq!(s.try_to_vec().unwrap(), before);
+    }
+
+    const DAY: u64 = SECS_PER_DAY;
+    /// 2025-01-01 00:00:00 UTC, a day boundary.
+    const D0: u64 = 1_735_689_600;
+
+    #[test]
+    fn decay_halves_points_every_half_life() {
+        let wallet = PubKey::new([1; 32]);
+        let mut s = session(&wallet);
+        mine(&mut s, &wallet, 21, 5, D0 + 100);
+        let midday = D0 + DAY / 2;
+
+        assert_eq!(s.decayed_score(2, midday), 400.0);
+        assert_eq!(s.decayed_score(2, midday + 2 * DAY), 200.0);
+        assert_eq!(s.decayed_score(2, midday + 6 * DAY), 50.0);
+        // the raw score never decays
+        assert_eq!(s.get_score(), 400);
+    }
+
+    #[test]
+    fn decay_buckets_points_by_utc_day() {
+        let wallet = PubKey::new([1; 32]);
+        let mut s = session(&wallet);
+        // one second apart, but on two UTC days
+        mine(&mut s, &wallet, 18, 5, D0 + DAY - 1);
+        mine(&mut s, &wallet, 18, 6, D0 + DAY);
+        // and one later on the second day, in the same bucket
+        mine(&mut s, &wallet, 18, 7, D0 + DAY + 80_000);
+
+        // at the second day's midpoint the first day is one half-life old
+        assert_eq!(s.decayed_score(1, D0 + DAY + DAY / 2), 35.0 + 70.0 + 70.0);
+        assert_eq!(s.decayed_score(1, D0 + 2 * DAY + DAY / 2), 17.5 + 35.0 + 35.0);
     }
 }
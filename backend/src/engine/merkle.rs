@@ -17,33 +17,78 @@ fn hash_pair(left: &[u8], right: &[u8]) -> [u8; 64] {
     out
 }
 
+/// One tree level up. An odd last node is promoted unchanged rather than
+/// paired with itself, so a list and the same list with its last leaf
+/// repeated never share a root (CVE-2012-2459).
+#[cfg(feature = "proofs")]
+fn next_level(hashes: &[[u8; 64]]) -> Vec<[u8; 64]> {
+    hashes
+        .chunks(2)
+        .map(|pair| match pair {
+            [l, r] => hash_pair(l, r),
+            [odd] => *odd,
+            _ => unreachable!(),
+        })
+        .collect()
+}
+
 #[cfg(feature = "proofs")]
 pub fn compute_merkle_root(mut hashes: Vec<[u8; 64]>) -> [u8; 64] {
     if hashes.is_empty() { return [0u8; 64]; }
     while hashes.len() > 1 {
-        let mut next = Vec::with_capacity((hashes.len() + 1) / 2);
-        for i in (0..hashes.len()).step_by(2) {
-            let l = hashes[i];
-            let r = if i + 1 < hashes.len() { hashes[i + 1] } else { l };
-            next.push(hash_pair(&l, &r));
-        }
-        hashes = next;
+        hashes = next_level(&hashes);
     }
     hashes[0]
 }
 
+/// Number of levels above `leaf_count` leaves: the bits of a leaf index.
+#[cfg(feature = "proofs")]
+pub fn merkle_depth(mut leaf_count: usize) -> usize {
+    let mut depth = 0;
+    while leaf_count > 1 {
+        leaf_count = leaf_count.div_ceil(2);
+        depth += 1;
+    }
+    depth
+}
+
+/// Sibling path for `hashes[index]`, in the order `verify_merkle_proof` consumes it.
+/// Levels where the node is promoted have no sibling and add nothing.
+#[cfg(feature = "proofs")]
+pub fn compute_merkle_proof(mut hashes: Vec<[u8; 64]>, mut index: usize) -> Vec<[u8; 64]> {
+    let mut proof = Vec::new();
+    if index >= hashes.len() { return proof; }
+    while hashes.len() > 1 {
+        if let Some(sibling) = hashes.get(index ^ 1) {
+            proof.push(*sibling);
+        }
+        hashes = next_level(&hashes);
+        index /= 2;
+    }
+    proof
+}
+
+/// Checks that `proof` leads from `leaf`, at `index` of a tree over
+/// `leaf_count` leaves, to `merkle_root`, using up every sibling.
 #[cfg(feature = "proofs")]
 pub fn verify_merkle_proof(
     mut leaf: [u8; 64],
     proof: Vec<[u8; 64]>,
     merkle_root: [u8; 64],
     mut index: usize,
+    mut leaf_count: usize,
 ) -> bool {
-    for p in proof {
-        leaf = if index % 2 == 0 { hash_pair(&leaf, &p) } else { hash_pair(&p, &leaf) };
+    if index >= leaf_count { return false; }
+    let mut siblings = proof.into_iter();
+    while leaf_count > 1 {
+        if index ^ 1 < leaf_count {
+            let Some(p) = siblings.next() else { return false };
+            leaf = if index.is_multiple_of(2) { hash_pair(&leaf, &p) } else { hash_pair(&p, &leaf) };
+        }
         index /= 2;
+        leaf_count = leaf_count.div_ceil(2);
     }
-    leaf == merkle_root
+    siblings.next().is_none() && leaf == merkle_root
 }
 
 #[cfg(feature = "proofs")]
@@ -55,3 +100,59 @@ pub fn compute_leaf_from_wallet(pubkey: &PubKey) -> [u8; 64] {
     out.copy_from_slice(&result[..]);
     out
 }
+
+#[cfg(all(test, feature = "proofs"))]
+mod tests {
+    use super::*;
+
+    fn leaves(n: u8) -> Vec<[u8; 64]> {
+        (0..n).map(|i| compute_leaf_from_wallet(&PubKey::new([i; 32]))).collect()
+    }
+
+    #[test]
+    fn every_leaf_proves_into_the_root() {
+        for n in 1..=9u8 {
+            let leaves = leaves(n);
+            let root = compute_merkle_root(leaves.clone());
+            for (index, leaf) in leaves.iter().enumerate() {
+                let proof = compute_merkle_proof(leaves.clone(), index);
+                assert!(proof.len() <= merkle_depth(leaves.len()));
+                assert!(verify_merkle_proof(*leaf, proof.clone(), root, index, leaves.len()), "{n} leaves, #{index}");
+                // the same proof for another position or leaf does not verify
+                assert!(!verify_merkle_proof(*leaf, proof.clone(), root, index ^ 1, leaves.len()));
+                assert!(!verify_merkle_proof([0xff; 64], proof, root, index, leaves.len()));
+            }
+        }
+    }
+
+    #[test]
+    fn odd_node_is_promoted_not_duplicated() {
+        let three = leaves(3);
+        let mut four = three.clone();
+        four.push(three[2]);
+        assert_ne!(compute_merkle_root(three.clone()), compute_merkle_root(four));
+        assert_eq!(
+            compute_merkle_root(three.clone()),
+            hash_pair(&hash_pair(&three[0], &three[1]), &three[2])
+        );
+        // the promoted leaf skips level 0
+        assert_eq!(compute_merkle_proof(three.clone(), 2), vec![hash_pair(&three[0], &three[1])]);
+    }
+
+    #[test]
+    fn leftover_or_missing_siblings_fail() {
+        let leaves = leaves(5);
+        let root = compute_merkle_root(leaves.clone());
+        let mut proof = compute_merkle_proof(leaves.clone(), 1);
+        proof.push(leaves[0]);
+        assert!(!verify_merkle_proof(leaves[1], proof.clone(), root, 1, 5));
+        proof.truncate(proof.len() - 2);
+        assert!(!verify_merkle_proof(leaves[1], proof, root, 1, 5));
+    }
+
+    #[test]
+    fn depth_counts_levels_above_the_leaves() {
+        let depths: Vec<usize> = [0, 1, 2, 3, 4, 5, 8, 9].map(merkle_depth).to_vec();
+        assert_eq!(depths, vec![0, 0, 1, 2, 2, 3, 3, 4]);
+    }
+}
@@ -1,3 +1,8 @@
+pub mod achievement;
 pub mod game;
+pub mod guild;
 pub mod kdapp;    
 pub mod merkle;   
+pub mod rules;
+pub mod season;
+pub mod tier;
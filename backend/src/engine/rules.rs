@@ -0,0 +1,275 @@
+// backend/src/engine/rules.rs
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+};
+
+use blake2::{Blake2b512, Digest};
+
+use crate::engine::game::BonusRules;
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize};
+
+/// μ-levels the handlers accept at all.
+const MU_RANGE: std::ops::RangeInclusive<u8> = 1..=64;
+
+/// The scoring economy: points per μ-level for mined and witnessed blocks,
+/// plus optional decay and bonus rules.
+/// A table entry covers its μ and every higher μ up to the next entry, so
+/// `21 = 400` means "21 and above".
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScoringRules {
+    pub min_mu_level: u8,
+    #[serde(deserialize_with = "mu_table")]
+    pub mined: BTreeMap<u8, u32>,
+    #[serde(deserialize_with = "mu_table")]
+    pub witness: BTreeMap<u8, u32>,
+    /// Optional decay for the time-weighted leaderboard view.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decay: Option<Decay>,
+    /// Optional bonus for mined superblocks in consecutive windows.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub streak: Option<StreakRule>,
+    /// Optional bonus for a witness soon after the wallet's own mined block.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub combo: Option<ComboRule>,
+}
+
+/// Points lose half their weight every `half_life_days`. Only the decayed
+/// leaderboard view uses this; raw scores never change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Decay {
+    pub half_life_days: u32,
+}
+
+/// A mined superblock in the window right after the previous one extends
+/// the streak; from the second window on, the first block of each window
+/// earns `points_per_window` times the windows so far beyond the first,
+/// capped at `max_points`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StreakRule {
+    pub window_hours: u32,
+    pub points_per_window: u32,
+    pub max_points: u32,
+}
+
+impl StreakRule {
+    pub fn window_secs(&self) -> u64 {
+        self.window_hours as u64 * 3600
+    }
+
+    /// Bonus for reaching a streak of `windows`; `None` below two.
+    pub fn points(&self, windows: u32) -> Option<u32> {
+        let extra = windows.checked_sub(1).filter(|&n| n > 0)?;
+        Some(extra.saturating_mul(self.points_per_window).min(self.max_points))
+    }
+}
+
+/// A witness within `within_minutes` of the wallet's last mined superblock
+/// earns `points`, once per mined block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ComboRule {
+    pub within_minutes: u32,
+    pub points: u32,
+}
+
+impl ComboRule {
+    pub fn within_secs(&self) -> u64 {
+        self.within_minutes as u64 * 60
+    }
+}
+
+/// Table keys arrive as strings in both JSON and TOML (`"21" = 400`).
+fn mu_table<'de, D: Deserializer<'de>>(d: D) -> Result<BTreeMap<u8, u32>, D::Error> {
+    BTreeMap::<String, u32>::deserialize(d)?
+        .into_iter()
+        .map(|(mu, points)| match mu.trim().parse() {
+            Ok(mu) => Ok((mu, points)),
+            Err(_) => Err(D::Error::custom(format!("μ-level key '{mu}' is not a number 0-255"))),
+        })
+        .collect()
+}
+
+impl Default for ScoringRules {
+    /// The original hard-coded table; witness points are half of mined.
+    fn default() -> Self {
+        let mined: BTreeMap<u8, u32> =
+            [(15, 15), (16, 25), (17, 40), (18, 70), (19, 120), (20, 200), (21, 400)].into();
+        let witness = mined.iter().map(|(&mu, &p)| (mu, p / 2)).collect();
+        Self { min_mu_level: 15, mined, witness, decay: None, streak: None, combo: None }
+    }
+}
+
+impl ScoringRules {
+    /// Reads a `.toml` file, or JSON for any other extension, and validates it.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        let rules: Self = if path.extension().is_some_and(|e| e == "toml") {
+            toml::from_str(&text).map_err(|e| format!("{}: {e}", path.display()))?
+        } else {
+            serde_json::from_str(&text).map_err(|e| format!("{}: {e}", path.display()))?
+        };
+        rules.validate()?;
+        Ok(rules)
+    }
+
+    /// Every scoring μ must get points, and more μ never earns less.
+    pub fn validate(&self) -> Result<(), String> {
+        if !MU_RANGE.contains(&self.min_mu_level) {
+            return Err(format!("min_mu_level {} is outside 1..=64", self.min_mu_level));
+        }
+        if self.decay.is_some_and(|d| d.half_life_days == 0) {
+            return Err("decay.half_life_days must be at least 1".into());
+        }
+        if self.streak.is_some_and(|r| r.window_hours == 0) {
+            return Err("streak.window_hours must be at least 1".into());
+        }
+        if self.combo.is_some_and(|r| r.within_minutes == 0) {
+            return Err("combo.within_minutes must be at least 1".into());
+        }
+        for (name, table) in [("mined", &self.mined), ("witness", &self.witness)] {
+            let Some((&first, _)) = table.first_key_value() else {
+                return Err(format!("{name} table is empty"));
+            };
+            if first > self.min_mu_level {
+                return Err(format!(
+                    "{name} table starts at μ {first}, above min_mu_level {}",
+                    self.min_mu_level
+                ));
+            }
+            if let Some(mu) = table.keys().find(|mu| !MU_RANGE.contains(mu)) {
+                return Err(format!("{name} table has μ {mu}, outside 1..=64"));
+            }
+            let mut prev = 0;
+            for (mu, &points) in table.range(self.min_mu_level..) {
+                if points < prev {
+                    return Err(format!("{name} points drop at μ {mu} ({points} < {prev})"));
+                }
+                prev = points;
+            }
+        }
+        Ok(())
+    }
+
+    /// Points for one event; 0 below `min_mu_level`.
+    pub fn points(&self, mu_level: u8, is_witness: bool) -> u32 {
+        if mu_level < self.min_mu_level {
+            return 0;
+        }
+        let table = if is_witness { &self.witness } else { &self.mined };
+        table.range(..=mu_level).next_back().map_or(0, |(_, &p)| p)
+    }
+
+    pub fn bonus_rules(&self) -> BonusRules {
+        BonusRules { streak: self.streak, combo: self.combo }
+    }
+
+    /// Short id of this table, stored on every record it scored.
+    pub fn id(&self) -> String {
+        let json = serde_json::to_vec(self).expect("rules serialize");
+        hex::encode(&Blake2b512::digest(json)[..8])
+    }
+}
+
+/// The rules in force, swappable at runtime. Commands take an `Arc` of the
+/// current table, so a reload never changes a command halfway through.
+pub struct RulesHandle {
+    path: Option<PathBuf>,
+    current: RwLock<Arc<ScoringRules>>,
+}
+
+impl RulesHandle {
+    /// `SCORING_RULES_PATH` (TOML or JSON); unset means the built-in table.
+    /// An invalid file is an error so the server refuses to start with it.
+    pub fn from_env() -> Result<Self, String> {
+        let path = std::env::var("SCORING_RULES_PATH").ok().filter(|p| !p.trim().is_empty());
+        Self::open(path.map(PathBuf::from))
+    }
+
+    /// Rules from `path`, or the built-in table without one.
+    pub fn open(path: Option<PathBuf>) -> Result<Self, String> {
+        let rules = match &path {
+            Some(p) => ScoringRules::load(p)?,
+            None => ScoringRules::default(),
+        };
+        Ok(Self { path, current: RwLock::new(Arc::new(rules)) })
+    }
+
+    pub fn current(&self) -> Arc<ScoringRules> {
+        self.current.read().unwrap().clone()
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Re-reads and validates the file without putting it in force.
+    pub fn read_file(&self) -> Result<ScoringRules, String> {
+        let path = self.path.as_ref().ok_or("SCORING_RULES_PATH is not set")?;
+        ScoringRules::load(path)
+    }
+
+    /// Puts `rules` in force for every command after this one.
+    pub fn replace(&self, rules: ScoringRules) -> Arc<ScoringRules> {
+        let rules = Arc::new(rules);
+        *self.current.write().unwrap() = rules.clone();
+        rules
+    }
+
+    /// Re-reads the file. On any error the old rules stay in force.
+    pub fn reload(&self) -> Result<Arc<ScoringRules>, String> {
+        Ok(self.replace(self.read_file()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::temp_dir;
+
+    const EXAMPLE: &str = include_str!("../../scoring_rules.example.toml");
+
+    #[test]
+    fn toml_and_json_files_load_the_same_table() {
+        let dir = temp_dir("rules-formats");
+        let toml_text = EXAMPLE
+            .replace("# [streak]", "[streak]")
+            .replace("# window_hours", "window_hours")
+            .replace("# points_per_window", "points_per_window")
+            .replace("# max_points", "max_points");
+        let json_text = serde_json::json!({
+            "min_mu_level": 15,
+            "streak": { "window_hours": 24, "points_per_window": 10, "max_points": 100 },
+            "mined": { "15": 15, "16": 25, "17": 40, "18": 70, "19": 120, "20": 200, "21": 400 },
+            "witness": { "15": 7, "16": 12, "17": 20, "18": 35, "19": 60, "20": 100, "21": 200 }
+        })
+        .to_string();
+        std::fs::write(dir.join("rules.toml"), toml_text).unwrap();
+        std::fs::write(dir.join("rules.json"), json_text).unwrap();
+
+        let from_toml = ScoringRules::load(&dir.join("rules.toml")).unwrap();
+        let from_json = ScoringRules::load(&dir.join("rules.json")).unwrap();
+        assert_eq!(from_toml, from_json);
+        assert_eq!(from_toml.id(), from_json.id());
+        assert_eq!(from_toml, ScoringRules { streak: from_toml.streak, ..ScoringRules::default() });
+    }
+
+    #[test]
+    fn validate_refuses_tables_that_leave_gaps_or_drop() {
+        let with = |edit: fn(&mut ScoringRules)| {
+            let mut rules = ScoringRules::default();
+            edit(&mut rules);
+            rules.validate().unwrap_err()
+        };
+        assert_eq!(with(|r| r.min_mu_level = 0), "min_mu_level 0 is outside 1..=64");
+        assert_eq!(with(|r| r.min_mu_level = 14), "mined table starts at μ 15, above min_mu_level 14");
+        assert_eq!(with(|r| { r.mined.insert(19, 10); }), "mined points drop at μ 19 (10 < 70)");
+        assert_eq!(with(|r| r.witness.clear()), "witness table is empty");
+        assert!(ScoringRules::default().validate().is_ok());
+    }
+}
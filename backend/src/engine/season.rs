@@ -0,0 +1,143 @@
+// backend/src/engine/season.rs
+
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// One competition window, `[starts_at, ends_at)`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Season {
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+}
+
+impl Season {
+    pub fn contains(&self, t: DateTime<Utc>) -> bool {
+        self.starts_at <= t && t < self.ends_at
+    }
+}
+
+/// The configured seasons, in time order and never overlapping.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SeasonCalendar {
+    #[serde(default)]
+    seasons: Vec<Season>,
+}
+
+impl SeasonCalendar {
+    /// `SEASONS_PATH` (TOML or JSON, a `seasons` list); unset means no seasons.
+    pub fn from_env() -> Result<Self, String> {
+        match std::env::var("SEASONS_PATH") {
+            Ok(p) if !p.trim().is_empty() => Self::load(Path::new(&p)),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        let mut cal: Self = if path.extension().is_some_and(|e| e == "toml") {
+            toml::from_str(&text).map_err(|e| format!("{}: {e}", path.display()))?
+        } else {
+            serde_json::from_str(&text).map_err(|e| format!("{}: {e}", path.display()))?
+        };
+        cal.seasons.sort_by_key(|s| s.starts_at);
+        cal.validate()?;
+        Ok(cal)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        for (i, s) in self.seasons.iter().enumerate() {
+            if s.id.trim().is_empty() {
+                return Err("a season has an empty id".into());
+            }
+            if s.starts_at >= s.ends_at {
+                return Err(format!("season '{}' ends before it starts", s.id));
+            }
+            if self.seasons[..i].iter().any(|o| o.id == s.id) {
+                return Err(format!("season id '{}' is used twice", s.id));
+            }
+            if let Some(prev) = i.checked_sub(1).map(|j| &self.seasons[j]) {
+                if prev.ends_at > s.starts_at {
+                    return Err(format!("seasons '{}' and '{}' overlap", prev.id, s.id));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub fn seasons(&self) -> &[Season] {
+        &self.seasons
+    }
+
+    pub fn get(&self, id: &str) -> Option<&Season> {
+        self.seasons.iter().find(|s| s.id == id)
+    }
+
+    /// The season running at `t`, if any.
+    pub fn at(&self, t: DateTime<Utc>) -> Option<&Season> {
+        self.seasons.iter().find(|s| s.contains(t))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use crate::testutil::temp_dir;
+
+    fn day(d: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 1, d, 0, 0, 0).unwrap()
+    }
+
+    fn calendar(json: serde_json::Value) -> Result<SeasonCalendar, String> {
+        let path = temp_dir("season-calendar").join("seasons.json");
+        std::fs::write(&path, json.to_string()).unwrap();
+        SeasonCalendar::load(&path)
+    }
+
+    #[test]
+    fn a_season_runs_from_its_start_up_to_its_end() {
+        let cal = calendar(serde_json::json!({ "seasons": [
+            { "id": "s2", "starts_at": day(10), "ends_at": day(20) },
+            { "id": "s1", "starts_at": day(1), "ends_at": day(10) },
+        ]}))
+        .unwrap();
+        let id = |t| cal.at(t).map(|s| s.id.as_str());
+        assert_eq!(id(day(1) - chrono::Duration::seconds(1)), None);
+        assert_eq!(id(day(1)), Some("s1"));
+        assert_eq!(id(day(10) - chrono::Duration::seconds(1)), Some("s1"));
+        // back-to-back seasons hand over at the boundary
+        assert_eq!(id(day(10)), Some("s2"));
+        assert_eq!(id(day(20)), None);
+        assert_eq!(cal.seasons()[0].id, "s1");
+    }
+
+    #[test]
+    fn load_refuses_overlapping_or_backwards_seasons() {
+        let err = |seasons: serde_json::Value| calendar(serde_json::json!({ "seasons": seasons })).unwrap_err();
+        assert_eq!(
+            err(serde_json::json!([
+                { "id": "a", "starts_at": day(1), "ends_at": day(11) },
+                { "id": "b", "starts_at": day(10), "ends_at": day(20) },
+            ])),
+            "seasons 'a' and 'b' overlap"
+        );
+        assert_eq!(
+            err(serde_json::json!([{ "id": "a", "starts_at": day(2), "ends_at": day(2) }])),
+            "season 'a' ends before it starts"
+        );
+        assert_eq!(
+            err(serde_json::json!([
+                { "id": "a", "starts_at": day(1), "ends_at": day(2) },
+                { "id": "a", "starts_at": day(3), "ends_at": day(4) },
+            ])),
+            "season id 'a' is used twice"
+        );
+    }
+}
@@ -0,0 +1,130 @@
+// backend/src/engine/tier.rs
+
+use serde::{Deserialize, Serialize};
+
+/// Default ladder, lowest first: `name=min_score` pairs.
+const DEFAULT_TIERS: &str =
+    "μScout=0,μForged=250,μLegend=1000,μMythic=5000,μHonorius=20000,μOracle=50000";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tier {
+    pub name: String,
+    pub min_score: u32,
+}
+
+/// Score thresholds for the tier titles. Sessions only keep a score; the tier
+/// is derived on read, so changing the thresholds needs no migration.
+#[derive(Debug, Clone)]
+pub struct TierTable {
+    tiers: Vec<Tier>, // ascending min_score, first is 0
+}
+
+/// A wallet crossed a tier threshold (either way).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TierChange {
+    pub from: String,
+    pub to: String,
+}
+
+impl Default for TierTable {
+    fn default() -> Self {
+        Self::parse(DEFAULT_TIERS).expect("default tiers are valid")
+    }
+}
+
+impl TierTable {
+    /// `TIER_THRESHOLDS`, e.g. `μScout=0,μForged=250,...`; unset means the defaults.
+    pub fn from_env() -> Result<Self, String> {
+        match std::env::var("TIER_THRESHOLDS") {
+            Ok(spec) if !spec.trim().is_empty() => Self::parse(&spec),
+            _ => Ok(Self::default()),
+        }
+    }
+
+    /// Parses `name=min_score` pairs. Thresholds must start at 0 and rise
+    /// strictly, so every score has exactly one tier.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let mut tiers: Vec<Tier> = Vec::new();
+        for part in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (name, min) = part
+                .split_once('=')
+                .ok_or_else(|| format!("'{part}' is not name=min_score"))?;
+            let name = name.trim();
+            if name.is_empty() {
+                return Err(format!("'{part}' has no tier name"));
+            }
+            let min_score: u32 = min
+                .trim()
+                .parse()
+                .map_err(|_| format!("'{part}' has an invalid min_score"))?;
+            if let Some(prev) = tiers.last() {
+                if min_score <= prev.min_score {
+                    return Err(format!("'{name}' must start above {} ('{}')", prev.min_score, prev.name));
+                }
+            } else if min_score != 0 {
+                return Err(format!("the first tier must start at 0, not {min_score}"));
+            }
+            tiers.push(Tier { name: name.to_string(), min_score });
+        }
+        if tiers.is_empty() {
+            return Err("no tiers given".into());
+        }
+        Ok(Self { tiers })
+    }
+
+    pub fn tiers(&self) -> &[Tier] {
+        &self.tiers
+    }
+
+    pub fn tier_for(&self, score: u32) -> &Tier {
+        let i = self.tiers.partition_point(|t| t.min_score <= score);
+        &self.tiers[i.saturating_sub(1)]
+    }
+
+    /// `Some` when going from `before` to `after` points lands in another tier.
+    pub fn change(&self, before: u32, after: u32) -> Option<TierChange> {
+        let (from, to) = (self.tier_for(before), self.tier_for(after));
+        (from != to).then(|| TierChange { from: from.name.clone(), to: to.name.clone() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn thresholds_are_inclusive_lower_bounds() {
+        let tiers = TierTable::default();
+        assert_eq!(tiers.tier_for(0).name, "μScout");
+        assert_eq!(tiers.tier_for(249).name, "μScout");
+        assert_eq!(tiers.tier_for(250).name, "μForged");
+        assert_eq!(tiers.tier_for(u32::MAX).name, "μOracle");
+
+        assert_eq!(tiers.change(249, 249), None);
+        assert_eq!(
+            tiers.change(249, 250),
+            Some(TierChange { from: "μScout".into(), to: "μForged".into() })
+        );
+        // a reorg can take a wallet back down
+        assert_eq!(
+            tiers.change(250, 249),
+            Some(TierChange { from: "μForged".into(), to: "μScout".into() })
+        );
+    }
+
+    #[test]
+    fn parse_refuses_tables_without_one_tier_per_score() {
+        let err = |spec: &str| TierTable::parse(spec).unwrap_err();
+        assert_eq!(err("a=10,b=20"), "the first tier must start at 0, not 10");
+        assert_eq!(err("a=0,b=20,c=20"), "'c' must start above 20 ('b')");
+        assert_eq!(err("a=0,b=20,c=5"), "'c' must start above 20 ('b')");
+        assert_eq!(err("a=0,b"), "'b' is not name=min_score");
+        assert_eq!(err("a=0,=5"), "'=5' has no tier name");
+        assert_eq!(err("a=0,b=-5"), "'b=-5' has an invalid min_score");
+        assert_eq!(err(" , "), "no tiers given");
+
+        let tiers = TierTable::parse(" a = 0 , b = 7 ").unwrap();
+        let names: Vec<(&str, u32)> = tiers.tiers().iter().map(|t| (t.name.as_str(), t.min_score)).collect();
+        assert_eq!(names, [("a", 0), ("b", 7)]);
+    }
+}
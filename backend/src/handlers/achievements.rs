@@ -0,0 +1,221 @@
+// backend/src/handlers/achievements.rs
+
+use axum::{extract::{Path, State}, http::StatusCode, Json};
+use chrono::{DateTime, TimeZone, Utc};
+use serde::Serialize;
+use std::sync::Arc;
+
+use crate::engine::achievement::{Achievement, AchievementBook};
+use crate::engine::kdapp::MueHeroSession;
+use crate::handlers::submission::{decode_pubkey_from_hex, Submission};
+use crate::state::{manager::Episodes, pki::PubKey, Sessions};
+use crate::store::{schema::SCHEMA_VERSION, SubmissionStore};
+
+#[derive(Serialize)]
+pub struct AchievementStatus {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    /// `None` while locked.
+    pub unlocked_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize)]
+pub struct WalletAchievements {
+    pub wallet: String,
+    pub unlocked: usize,
+    pub achievements: Vec<AchievementStatus>,
+}
+
+/// What a wallet has unlocked, against every configured achievement.
+/// Unlocks of achievements no longer configured are listed after them.
+pub async fn get_wallet_achievements(
+    State(sessions): State<Arc<Sessions>>,
+    State(book): State<Arc<AchievementBook>>,
+    Path(wallet): Path<String>,
+) -> Result<Json<WalletAchievements>, (StatusCode, String)> {
+    let pubkey = decode_pubkey_from_hex(&wallet)?;
+    let sessions = sessions.read();
+    let unlocked = sessions.get(&pubkey).map(|s| s.achievements().clone()).unwrap_or_default();
+    let at = |id: &str| unlocked.get(id).and_then(|&t| Utc.timestamp_opt(t as i64, 0).single());
+
+    let mut list: Vec<AchievementStatus> = book
+        .achievements()
+        .iter()
+        .map(|a| AchievementStatus {
+            id: a.id.clone(),
+            name: a.name.clone(),
+            description: a.description.clone(),
+            unlocked_at: at(&a.id),
+        })
+        .collect();
+    list.extend(unlocked.keys().filter(|id| book.get(id).is_none()).map(|id| AchievementStatus {
+        id: id.clone(),
+        name: id.clone(),
+        description: String::new(),
+        unlocked_at: at(id),
+    }));
+
+    Ok(Json(WalletAchievements { wallet, unlocked: unlocked.len(), achievements: list }))
+}
+
+/// What an unlock is credited to: the event that met the condition, or a
+/// season finish (no block).
+#[derive(Debug, Clone, Copy)]
+pub struct Trigger<'a> {
+    pub wallet: &'a str,
+    pub mu_level: u8,
+    pub block_height: u64,
+    pub season: Option<&'a str>,
+}
+
+impl<'a> From<&'a Submission> for Trigger<'a> {
+    fn from(s: &'a Submission) -> Self {
+        Self {
+            wallet: &s.wallet,
+            mu_level: s.mu_level,
+            block_height: s.block_height,
+            season: s.season.as_deref(),
+        }
+    }
+}
+
+/// Unlock record for `id`. It carries the trigger's block height, so a
+/// reorg that orphans the block reverts the unlock too.
+pub fn unlock_record(trigger: Trigger, id: &str, at: DateTime<Utc>) -> Submission {
+    Submission {
+        wallet: trigger.wallet.to_string(),
+        score: 0,
+        mu_level: trigger.mu_level,
+        block_height: trigger.block_height,
+        date_mined: at,
+        event_type: "achievement".into(),
+        schema_version: SCHEMA_VERSION,
+        seq: None,
+        prev_hash: None,
+        idempotency_key: None,
+        reverts: None,
+        tier_change: None,
+        rules: None,
+        season: trigger.season.map(str::to_string),
+        bonuses: None,
+        achievement: Some(id.to_string()),
+        guild: None,
+    }
+}
+
+/// Unlocks each of `found` in `pubkey`'s session and logs an unlock record
+/// for it. The triggering event is already committed, so a failed append
+/// only takes that unlock back (it is retried on the wallet's next event).
+/// Returns the ids unlocked.
+pub fn unlock_and_log(
+    store: &dyn SubmissionStore,
+    sessions: &mut Episodes<'_, MueHeroSession, PubKey>,
+    pubkey: &PubKey,
+    found: &[&Achievement],
+    trigger: Trigger,
+) -> Vec<String> {
+    let now = Utc::now();
+    let mut unlocked = Vec::new();
+    for a in found {
+        let Some(session) = sessions.get_mut(pubkey) else { break };
+        if !session.unlock(&a.id, now.timestamp().max(0) as u64) {
+            continue;
+        }
+        match store.append(&unlock_record(trigger, &a.id, now)) {
+            Ok(_) => {
+                println!("🎖️ {} unlocked '{}'", trigger.wallet, a.name);
+                unlocked.push(a.id.clone());
+            }
+            Err(e) => {
+                eprintln!("⚠️  Could not log unlock '{}' for {}: {e}", a.id, trigger.wallet);
+                session.relock(&a.id);
+            }
+        }
+    }
+    unlocked
+}
+
+/// Event achievements the wallet now meets, unlocked and logged.
+pub fn check_event_achievements(
+    store: &dyn SubmissionStore,
+    sessions: &mut Episodes<'_, MueHeroSession, PubKey>,
+    book: &AchievementBook,
+    pubkey: &PubKey,
+    trigger: Trigger,
+) -> Vec<String> {
+    let found = match sessions.get(pubkey) {
+        Some(s) => book.newly_met(s.stats(), |id| s.is_unlocked(id)),
+        None => return Vec::new(),
+    };
+    unlock_and_log(store, sessions, pubkey, &found, trigger)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::season::SeasonCalendar;
+    use crate::handlers::reorg::orphan_blocks;
+    use crate::handlers::submission::{replay_into_sessions, AppState};
+    use crate::state::Guilds;
+    use crate::testutil::{app_state, mine, temp_dir, wallet};
+
+    fn unlocked(sessions: &Sessions, wallet: &str) -> Vec<String> {
+        let pk = decode_pubkey_from_hex(wallet).unwrap();
+        sessions.read().get(&pk).map(|s| s.achievements().keys().cloned().collect()).unwrap_or_default()
+    }
+
+    fn replayed(app: &AppState) -> Sessions {
+        let (sessions, guilds) = (Sessions::new(), Guilds::new());
+        let rules = app.rules.current();
+        let calendar = SeasonCalendar::default();
+        replay_into_sessions(app.store.as_ref(), &sessions, &guilds, &rules, &app.rules_history, &calendar, 0)
+            .unwrap();
+        sessions
+    }
+
+    fn unlock_records(app: &AppState, wallet: &str) -> usize {
+        app.store.by_wallet(wallet).unwrap().iter().filter(|s| s.event_type == "achievement").count()
+    }
+
+    #[tokio::test]
+    async fn an_achievement_unlocks_once_and_replays_from_its_record() {
+        let dir = temp_dir("achievements-once");
+        let app = app_state(&dir);
+        let a = wallet('a');
+
+        let newly = |res: serde_json::Value| res["achievements_unlocked"].clone();
+        assert_eq!(newly(mine(&app, &a, None, 17, 4).await.unwrap()), serde_json::json!([]));
+        assert_eq!(newly(mine(&app, &a, None, 18, 5).await.unwrap()), serde_json::json!(["first_mu18"]));
+        assert_eq!(newly(mine(&app, &a, None, 19, 6).await.unwrap()), serde_json::json!([]));
+        assert_eq!(unlock_records(&app, &a), 1);
+
+        let Json(status) =
+            get_wallet_achievements(State(app.sessions.clone()), State(app.achievements.clone()), Path(a.clone()))
+                .await
+                .unwrap();
+        assert_eq!(status.unlocked, 1);
+        let live = app.sessions.read()[&decode_pubkey_from_hex(&a).unwrap()].achievements().clone();
+        let sessions = replayed(&app);
+        assert_eq!(sessions.read()[&decode_pubkey_from_hex(&a).unwrap()].achievements(), &live);
+    }
+
+    #[tokio::test]
+    async fn a_reorg_relocks_what_its_blocks_unlocked() {
+        let dir = temp_dir("achievements-reorg");
+        let app = app_state(&dir);
+        let a = wallet('a');
+        mine(&app, &a, None, 16, 4).await.unwrap();
+        mine(&app, &a, None, 21, 5).await.unwrap();
+        assert_eq!(unlocked(&app.sessions, &a), ["first_mu18", "honorius"]);
+
+        orphan_blocks(app.store.as_ref(), &app.sessions, &app.guilds, &app.tiers, 5).unwrap();
+        assert!(unlocked(&app.sessions, &a).is_empty());
+        assert!(unlocked(&replayed(&app), &a).is_empty());
+
+        // earning it again after the reorg unlocks (and logs) it again
+        mine(&app, &a, None, 18, 5).await.unwrap();
+        assert_eq!(unlocked(&app.sessions, &a), ["first_mu18"]);
+        assert_eq!(unlocked(&replayed(&app), &a), ["first_mu18"]);
+    }
+}
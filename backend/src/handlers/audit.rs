@@ -0,0 +1,22 @@
+// backend/src/handlers/audit.rs
+
+use axum::{extract::State, http::{HeaderMap, StatusCode}, Json};
+
+use crate::handlers::health::HealthState;
+use crate::handlers::submission::{store_error, AppState};
+use crate::store::chain::{verify_chain, ChainReport};
+
+/// Walks the hash-chained log and reports its head and the first broken link.
+/// Reads every record, so it is admin-only.
+pub async fn get_audit_chain(
+    State((app, h)): State<(AppState, HealthState)>,
+    headers: HeaderMap,
+) -> Result<Json<ChainReport>, (StatusCode, String)> {
+    let need = h.admin_token.clone().unwrap_or_default();
+    let got = headers.get("x-admin-token").and_then(|v| v.to_str().ok()).unwrap_or("");
+    if need.is_empty() || need != got {
+        return Err((StatusCode::UNAUTHORIZED, "unauthorized".into()));
+    }
+    let report = verify_chain(app.store.as_ref()).map_err(store_error)?;
+    Ok(Json(report))
+}
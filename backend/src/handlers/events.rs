@@ -7,16 +7,21 @@ use axum::{
     Json,
 };
 use blake2::{Blake2b512, Digest};
+use std::collections::HashSet;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use crate::handlers::submission::SharedState;
+use crate::engine::game::Bonus;
+use crate::engine::tier::TierChange;
+use crate::handlers::submission::{store_error, SharedState};
+use crate::store::schema::parse_timestamp;
 
 #[derive(Deserialize)]
 pub struct EventsQuery {
     #[serde(default = "default_limit")]
     pub limit: usize,
     pub wallet: Option<String>,
-    pub since: Option<String>, // ISO
+    pub since: Option<String>, // RFC 3339 or any legacy log format
     #[serde(default = "default_order")]
     pub order: String,         // "desc" | "asc"
 }
@@ -28,10 +33,30 @@ pub struct EnrichedEvent {
     pub wallet: String,
     pub mu_level: u8,
     pub block_height: u64,
-    pub date_mined: String,
+    pub date_mined: DateTime<Utc>,
     pub event_type: String,
+    /// The record's `score`: points awarded, bonuses included (taken back,
+    /// on a revert); 0 on unlock and guild rows.
     pub score_delta: u32,
     pub command: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seq: Option<u64>,
+    /// On a revert record: `seq` of the orphaned event it cancels.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reverts: Option<u64>,
+    /// A later revert record cancelled this event (its block was reorged out).
+    pub reverted: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tier_change: Option<TierChange>,
+    /// Bonus line items the engine awarded with this event.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bonuses: Option<Vec<Bonus>>,
+    /// On unlock rows: the achievement id.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub achievement: Option<String>,
+    /// On guild rows: the guild joined or left; on scored rows: the guild credited.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub guild: Option<String>,
 }
 
 pub async fn get_events(
@@ -39,36 +64,64 @@ pub async fn get_events(
     Query(q): Query<EventsQuery>,
     headers: HeaderMap,
 ) -> impl IntoResponse {
-    let mut list = state.read().unwrap().clone();
+    let since = match q.since.as_deref().map(parse_timestamp) {
+        None => None,
+        Some(Some(t)) => Some(t),
+        Some(None) => return (StatusCode::BAD_REQUEST, "invalid since").into_response(),
+    };
 
-    if let Some(w) = &q.wallet {
-        list.retain(|s| &s.wallet == w);
-    }
-    if let Some(since) = &q.since {
-        list.retain(|s| s.date_mined > *since);
+    // let the store use its index for the most selective filter
+    let fetched = match (&q.wallet, since) {
+        (Some(w), _) => state.by_wallet(w),
+        (None, Some(since)) => state.by_time_range(Some(since), None),
+        (None, None) => state.all(),
+    };
+    let mut list = match fetched {
+        Ok(list) => list,
+        Err(e) => return store_error(e).into_response(),
+    };
+
+    if let (Some(_), Some(since)) = (&q.wallet, since) {
+        list.retain(|s| s.date_mined > since);
     }
 
     if q.order.to_lowercase() == "asc" {
-        list.sort_by(|a,b| a.date_mined.cmp(&b.date_mined));
+        list.sort_by_key(|s| s.date_mined);
     } else {
-        list.sort_by(|a,b| b.date_mined.cmp(&a.date_mined));
+        list.sort_by_key(|s| std::cmp::Reverse(s.date_mined));
     }
 
+    // revert records are always newer than what they cancel, so any
+    // original in the list has its revert (if any) in the list too
+    let reverted: HashSet<u64> = list.iter().filter_map(|s| s.reverts).collect();
+
     if list.len() > q.limit {
         list.truncate(q.limit);
     }
 
     let enriched: Vec<EnrichedEvent> = list.into_iter().map(|s| {
-        let (command, score_delta) = if s.event_type == "witness" {
-            ("WitnessPoints".to_string(), (s.mu_level as u32) / 2)
+        let (command, score_delta) = if s.reverts.is_some() {
+            ("Revert".to_string(), s.score)
+        } else if s.achievement.is_some() {
+            ("Unlock".to_string(), 0)
+        } else if s.event_type == "guild_join" {
+            ("GuildJoin".to_string(), 0)
+        } else if s.event_type == "guild_leave" {
+            ("GuildLeave".to_string(), 0)
+        } else if s.event_type == "witness" {
+            ("WitnessPoints".to_string(), s.score)
         } else {
-            ("AddPoints".to_string(), s.mu_level as u32)
+            ("AddPoints".to_string(), s.score)
         };
         EnrichedEvent { wallet: s.wallet, mu_level: s.mu_level, block_height: s.block_height,
-                        date_mined: s.date_mined, event_type: s.event_type, score_delta, command }
+                        date_mined: s.date_mined, event_type: s.event_type, score_delta, command,
+                        seq: s.seq, reverts: s.reverts,
+                        reverted: s.seq.is_some_and(|n| reverted.contains(&n)),
+                        tier_change: s.tier_change, bonuses: s.bonuses,
+                        achievement: s.achievement, guild: s.guild }
     }).collect();
 
-    let next_since = enriched.first().map(|s| s.date_mined.clone());
+    let next_since = enriched.first().map(|s| s.date_mined);
     let body = serde_json::json!({ "events": enriched, "next_since": next_since });
 
     // Weak ETag over body
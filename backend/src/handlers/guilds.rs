@@ -0,0 +1,271 @@
+// backend/src/handlers/guilds.rs
+
+use axum::{extract::{Path, State}, http::{HeaderMap, StatusCode}, Json};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::engine::guild::{valid_guild_name, GuildCommand, GuildError, GuildSession};
+use crate::episode::{EpisodeError, PayloadMetadata};
+use crate::handlers::submission::{decode_pubkey_from_hex, store_error, ApiError, AppState, Submission};
+use crate::state::{manager::{CommitError, Episodes}, pki::PubKey, Guilds};
+use crate::store::{schema::SCHEMA_VERSION, StoreError};
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GuildReq {
+    pub wallet: String, // hex, same identity as mining
+}
+
+#[derive(Debug, Serialize)]
+pub struct GuildEntry {
+    pub guild: String,
+    pub score: u64,
+    pub members: usize,
+    pub rank: usize,
+    /// 🥇🥈🥉 for the top three.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub medal: Option<String>,
+}
+
+/// The guild `wallet` is in, if any. A wallet is in at most one guild.
+pub fn guild_of(guilds: &Episodes<'_, GuildSession, String>, wallet: &PubKey) -> Option<String> {
+    guilds.iter().find(|(_, g)| g.is_member(wallet)).map(|(name, _)| name.clone())
+}
+
+/// Credits `points` a member just scored to `guild`. Call with the sessions
+/// lock held, right after the event committed.
+pub fn credit_guild(
+    guilds: &mut Episodes<'_, GuildSession, String>,
+    guild: Option<&str>,
+    member: &PubKey,
+    points: u32,
+    meta: &PayloadMetadata,
+) {
+    let Some(name) = guild.filter(|_| points > 0) else { return };
+    let cmd = GuildCommand::Credit { member: member.clone(), points };
+    if let Err(e) = guilds.execute(&name.to_string(), &[], &cmd, Some(member.clone()), meta) {
+        eprintln!("⚠️  Guild '{name}' credit of {points} refused: {e:?}");
+    }
+}
+
+pub fn guild_error(e: EpisodeError<GuildError>) -> ApiError {
+    let cmd = match e {
+        EpisodeError::CommandError(cmd) => cmd,
+        EpisodeError::InternalError(msg) => {
+            return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "internal_error", msg)
+        }
+    };
+    let message = cmd.to_string();
+    match cmd {
+        GuildError::AlreadyMember => ApiError::new(StatusCode::CONFLICT, "already_member", message),
+        GuildError::NotAMember => ApiError::new(StatusCode::CONFLICT, "not_a_member", message),
+        GuildError::UnauthorizedSigner => ApiError::new(StatusCode::FORBIDDEN, "unauthorized_signer", message),
+        GuildError::AlreadyFounded => ApiError::new(StatusCode::CONFLICT, "already_founded", message),
+    }
+}
+
+fn membership_record(wallet: &str, guild: &str, event_type: &str) -> Submission {
+    Submission {
+        wallet: wallet.to_string(),
+        score: 0,
+        mu_level: 0,
+        block_height: 0,
+        date_mined: Utc::now(),
+        event_type: event_type.into(),
+        schema_version: SCHEMA_VERSION,
+        seq: None,
+        prev_hash: None,
+        idempotency_key: None,
+        reverts: None,
+        tier_change: None,
+        rules: None,
+        season: None,
+        bonuses: None,
+        achievement: None,
+        guild: Some(guild.to_string()),
+    }
+}
+
+pub async fn post_join(
+    State(app): State<AppState>,
+    headers: HeaderMap,
+    Path(name): Path<String>,
+    Json(req): Json<GuildReq>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    membership(app, headers, name, req, true).await
+}
+
+pub async fn post_leave(
+    State(app): State<AppState>,
+    headers: HeaderMap,
+    Path(name): Path<String>,
+    Json(req): Json<GuildReq>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    membership(app, headers, name, req, false).await
+}
+
+/// Join (founding the guild if it does not exist yet) or leave, logged as
+/// a `guild_join`/`guild_leave` record.
+async fn membership(
+    app: AppState,
+    headers: HeaderMap,
+    name: String,
+    req: GuildReq,
+    join: bool,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    // auth
+    let expected = std::env::var("MUE_SECRET").unwrap_or_default();
+    let got = headers.get("X-MUE-KEY").and_then(|h| h.to_str().ok()).unwrap_or("");
+    if expected.is_empty() || got != expected {
+        return Err((StatusCode::UNAUTHORIZED, "bad secret".to_string()).into());
+    }
+    if !valid_guild_name(&name) {
+        return Err(ApiError::new(
+            StatusCode::BAD_REQUEST,
+            "invalid_guild_name",
+            "guild names are 1-32 letters, digits, '-' or '_'",
+        ));
+    }
+    let pubkey = decode_pubkey_from_hex(&req.wallet)?;
+    let meta = PayloadMetadata { accepting_time: Utc::now().timestamp().max(0) as u64 };
+
+    // sessions first (lock order), so snapshots see the record and the
+    // guild change together
+    let _sessions = app.sessions.write();
+    let mut guilds = app.guilds.write();
+    let cmd = match (join, guild_of(&guilds, &pubkey)) {
+        (true, Some(other)) if other != name => {
+            return Err(ApiError::new(
+                StatusCode::CONFLICT,
+                "in_other_guild",
+                format!("wallet is already in guild '{other}'"),
+            ));
+        }
+        (true, _) if guilds.get(&name).is_none() => GuildCommand::Found { member: pubkey.clone() },
+        (true, _) => GuildCommand::Join { member: pubkey.clone() },
+        (false, _) if guilds.get(&name).is_none() => {
+            return Err(ApiError::new(StatusCode::NOT_FOUND, "unknown_guild", format!("no guild '{name}'")));
+        }
+        (false, _) => GuildCommand::Leave { member: pubkey.clone() },
+    };
+    let event_type = if join { "guild_join" } else { "guild_leave" };
+    let record = membership_record(&req.wallet, &name, event_type);
+    let link = guilds
+        .commit(&name, std::slice::from_ref(&pubkey), &cmd, Some(pubkey.clone()), &meta, |_| {
+            app.store.append(&record)
+        })
+        .map_err(|e: CommitError<GuildError, StoreError>| match e {
+            CommitError::Command(e) => guild_error(e),
+            CommitError::Persist(e) => store_error(e).into(),
+        })?;
+
+    let members = guilds.get(&name).map_or(0, |g| g.members().len());
+    let verb = match cmd {
+        GuildCommand::Found { .. } => "founded",
+        GuildCommand::Join { .. } => "joined",
+        _ => "left",
+    };
+    println!("🛡️ {} {verb} guild '{name}' ({members} members)", req.wallet);
+    Ok(Json(serde_json::json!({ "ok": true, "guild": name, "members": members, "seq": link.seq })))
+}
+
+/// Guilds ranked by score; guilds that everyone left still rank.
+pub async fn get_guild_leaderboard(State(guilds): State<Arc<Guilds>>) -> Json<Vec<GuildEntry>> {
+    let guilds = guilds.read();
+    let mut entries: Vec<GuildEntry> = guilds
+        .iter()
+        .map(|(name, g)| GuildEntry {
+            guild: name.clone(),
+            score: g.score(),
+            members: g.members().len(),
+            rank: 0,
+            medal: None,
+        })
+        .collect();
+    entries.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.guild.cmp(&b.guild)));
+    for (i, e) in entries.iter_mut().enumerate() {
+        e.rank = i + 1;
+        e.medal = match i {
+            0 => Some("🥇".into()),
+            1 => Some("🥈".into()),
+            2 => Some("🥉".into()),
+            _ => None,
+        };
+    }
+    Json(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    use crate::handlers::reorg::orphan_blocks;
+    use crate::testutil::{app_state, mine, temp_dir, wallet, SECRET};
+
+    async fn member(app: &AppState, wallet: &str, guild: &str, join: bool) -> Result<serde_json::Value, ApiError> {
+        let mut headers = HeaderMap::new();
+        headers.insert("X-MUE-KEY", HeaderValue::from_static(SECRET));
+        let (path, req) = (Path(guild.to_string()), Json(GuildReq { wallet: wallet.to_string() }));
+        let res = if join {
+            post_join(State(app.clone()), headers, path, req).await
+        } else {
+            post_leave(State(app.clone()), headers, path, req).await
+        };
+        res.map(|Json(v)| v)
+    }
+
+    fn guild_score(app: &AppState, guild: &str) -> u64 {
+        app.guilds.read().get(guild).map_or(0, |g| g.score())
+    }
+
+    #[tokio::test]
+    async fn wallets_found_join_and_leave_one_guild_at_a_time() {
+        let dir = temp_dir("guilds-membership");
+        let app = app_state(&dir);
+        let (a, b) = (wallet('a'), wallet('b'));
+
+        assert_eq!(member(&app, &a, "miners", true).await.unwrap()["members"], 1);
+        assert_eq!(member(&app, &b, "miners", true).await.unwrap()["members"], 2);
+        let err = member(&app, &a, "other", true).await.unwrap_err();
+        assert_eq!((err.status, err.body["error"].as_str()), (StatusCode::CONFLICT, Some("in_other_guild")));
+        let err = member(&app, &a, "miners", true).await.unwrap_err();
+        assert_eq!(err.body["error"], "already_member");
+        let err = member(&app, &a, "nobody", false).await.unwrap_err();
+        assert_eq!((err.status, err.body["error"].as_str()), (StatusCode::NOT_FOUND, Some("unknown_guild")));
+        assert_eq!(member(&app, &a, "bad name!", true).await.unwrap_err().status, StatusCode::BAD_REQUEST);
+
+        assert_eq!(member(&app, &a, "miners", false).await.unwrap()["members"], 1);
+        let err = member(&app, &a, "miners", false).await.unwrap_err();
+        assert_eq!(err.body["error"], "not_a_member");
+        // free to go elsewhere now
+        member(&app, &a, "other", true).await.unwrap();
+        let logged = app.store.by_wallet(&a).unwrap();
+        let kinds: Vec<&str> = logged.iter().map(|s| s.event_type.as_str()).collect();
+        assert_eq!(kinds, ["guild_join", "guild_leave", "guild_join"]);
+    }
+
+    #[tokio::test]
+    async fn guild_keeps_credit_after_a_leave_and_loses_it_to_a_reorg() {
+        let dir = temp_dir("guilds-credit");
+        let app = app_state(&dir);
+        let (a, b) = (wallet('a'), wallet('b'));
+        member(&app, &a, "miners", true).await.unwrap();
+        member(&app, &b, "miners", true).await.unwrap();
+
+        let kept = mine(&app, &a, None, 18, 5).await.unwrap()["points_awarded"].as_u64().unwrap();
+        member(&app, &a, "miners", false).await.unwrap();
+        // scored outside the guild: not credited
+        mine(&app, &a, None, 18, 6).await.unwrap();
+        assert_eq!(guild_score(&app, "miners"), kept);
+
+        let lost = mine(&app, &b, None, 17, 8).await.unwrap()["points_awarded"].as_u64().unwrap();
+        assert_eq!(guild_score(&app, "miners"), kept + lost);
+        orphan_blocks(app.store.as_ref(), &app.sessions, &app.guilds, &app.tiers, 8).unwrap();
+        assert_eq!(guild_score(&app, "miners"), kept);
+
+        let Json(board) = get_guild_leaderboard(State(app.guilds.clone())).await;
+        assert_eq!((board[0].guild.as_str(), board[0].score, board[0].members), ("miners", kept, 1));
+    }
+}
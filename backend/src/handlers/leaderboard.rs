@@ -1,18 +1,51 @@
 // backend/src/handlers/leaderboard.rs
 
-use axum::{extract::State, Json};
-use crate::handlers::submission::{SharedState, Submission};
-use crate::state::SESSIONS;
+use axum::{extract::{Query, State}, http::StatusCode, Json};
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
-#[derive(serde::Serialize)]
+use crate::engine::rules::RulesHandle;
+use crate::engine::season::Season;
+use crate::engine::tier::TierTable;
+use crate::state::{pki::PubKey, seasons::Seasons, Sessions};
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct LeaderboardEntry {
     pub wallet_tag: String,
     pub score: u32,
     pub rank: usize,
     pub mu_level: u8,
     pub block_height: u64,
-    pub date_mined: String,
+    pub date_mined: Option<DateTime<Utc>>,
+    /// From the all-time score in every view, like tier-change events.
     pub tier: String,
+    /// Undecayed all-time score, only in the decayed view.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub raw_score: Option<u32>,
+    /// Session key, for callers that act on the standings; not serialized.
+    #[serde(skip)]
+    pub wallet: Option<PubKey>,
+    /// 🥇🥈🥉 for the top three.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub medal: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct LeaderboardQuery {
+    /// Season id; omitted means all-time.
+    pub season: Option<String>,
+    /// `raw` (default) or `decayed`.
+    pub view: Option<String>,
+}
+
+/// What a leaderboard ranks by.
+#[derive(Debug, Clone, Copy)]
+pub enum Ranking<'a> {
+    AllTime,
+    Season(&'a Season),
+    /// All-time points weighted by age at `now` (unix seconds).
+    Decayed { half_life_days: u32, now: u64 },
 }
 
 fn tag_wallet(addr: &str) -> String {
@@ -22,44 +55,141 @@ fn tag_wallet(addr: &str) -> String {
     format!("{start}...{end}")
 }
 
-pub async fn get_leaderboard(State(state): State<SharedState>) -> Json<Vec<LeaderboardEntry>> {
-    let sessions = SESSIONS.read().unwrap();
-    let submissions = state.read().unwrap();
+pub async fn get_leaderboard(
+    State(sessions): State<Arc<Sessions>>,
+    State(tiers): State<Arc<TierTable>>,
+    State(seasons): State<Arc<Seasons>>,
+    State(rules): State<Arc<RulesHandle>>,
+    Query(q): Query<LeaderboardQuery>,
+) -> Result<Json<Vec<LeaderboardEntry>>, (StatusCode, String)> {
+    let decayed = match q.view.as_deref() {
+        None | Some("raw") => false,
+        Some("decayed") => true,
+        Some(v) => return Err((StatusCode::BAD_REQUEST, format!("unknown view '{v}'"))),
+    };
+    if decayed {
+        if q.season.is_some() {
+            return Err((StatusCode::BAD_REQUEST, "the decayed view is all-time only".into()));
+        }
+        let decay = rules
+            .current()
+            .decay
+            .ok_or((StatusCode::BAD_REQUEST, "no decay rule is configured".into()))?;
+        let ranking = Ranking::Decayed {
+            half_life_days: decay.half_life_days,
+            now: Utc::now().timestamp().max(0) as u64,
+        };
+        return Ok(Json(build_leaderboard(&sessions, &tiers, ranking)));
+    }
+
+    let Some(id) = q.season else {
+        return Ok(Json(build_leaderboard(&sessions, &tiers, Ranking::AllTime)));
+    };
+    let season = seasons
+        .calendar()
+        .get(&id)
+        .ok_or((StatusCode::NOT_FOUND, format!("unknown season '{id}'")))?;
 
-    // Group submissions by wallet so we can pull metadata
-    let mut entries: Vec<LeaderboardEntry> = sessions.iter().map(|(wallet, session)| {
-        // Find this wallet’s submissions
-        let mut wallet_subs: Vec<&Submission> = submissions
-            .iter()
-            .filter(|s| s.wallet == hex::encode(wallet.as_bytes()))
-            .collect();
+    // ended seasons are served from their frozen standings
+    if seasons.is_archived(&id) {
+        let archive = seasons.read_archive(&id).map_err(|e| {
+            (StatusCode::INTERNAL_SERVER_ERROR, format!("season archive error: {e}"))
+        })?;
+        if let Some(archive) = archive {
+            return Ok(Json(archive.standings));
+        }
+    }
+    Ok(Json(build_leaderboard(&sessions, &tiers, Ranking::Season(season))))
+}
 
-        // Pull latest submission for metadata
-        wallet_subs.sort_by(|a, b| b.date_mined.cmp(&a.date_mined));
-        let latest = wallet_subs.first();
+/// Ranks the current sessions, with metadata from each wallet's latest
+/// scored event. For a season, ranks by points in that season, among wallets
+/// that played it, and takes metadata from its events. The decayed view
+/// ranks by decayed points (rounded) and reports the raw score alongside.
+pub fn build_leaderboard(sessions: &Sessions, tiers: &TierTable, ranking: Ranking) -> Vec<LeaderboardEntry> {
+    let season = match ranking {
+        Ranking::Season(se) => Some(se.id.as_str()),
+        _ => None,
+    };
+    let sessions = sessions.read();
 
-        LeaderboardEntry {
+    let mut entries: Vec<LeaderboardEntry> = sessions.iter().filter_map(|(wallet, session)| {
+        let (score, raw_score) = match ranking {
+            Ranking::AllTime => (session.get_score(), None),
+            Ranking::Season(se) => (session.season_score(&se.id)?, None),
+            Ranking::Decayed { half_life_days, now } => {
+                let decayed = session.decayed_score(half_life_days, now).round() as u32;
+                (decayed, Some(session.get_score()))
+            }
+        };
+        let last = session.last_event(season);
+
+        Some(LeaderboardEntry {
             wallet_tag: tag_wallet(&wallet.to_string()),
-            score: session.get_score(),
-            mu_level: latest.map(|s| s.mu_level).unwrap_or(0),
-            block_height: latest.map(|s| s.block_height).unwrap_or(0),
-            date_mined: latest.map(|s| s.date_mined.clone()).unwrap_or_default(),
+            score,
+            mu_level: last.map_or(0, |l| l.mu_level),
+            block_height: last.map_or(0, |l| l.block_height),
+            date_mined: last.and_then(|l| Utc.timestamp_opt(l.at as i64, 0).single()),
             rank: 0,
-            tier: session.get_rank(),
-        }
+            tier: session.tier(tiers).name.clone(),
+            raw_score,
+            wallet: Some(wallet.clone()),
+            medal: None,
+        })
     }).collect();
 
     // Sort and assign ranks + medals
     entries.sort_by(|a,b| b.score.cmp(&a.score).then(b.date_mined.cmp(&a.date_mined)));
     for (i, e) in entries.iter_mut().enumerate() {
         e.rank = i + 1;
-        e.tier = match i {
-            0 => format!("🥇 {}", e.tier),
-            1 => format!("🥈 {}", e.tier),
-            2 => format!("🥉 {}", e.tier),
-            _ => e.tier.clone(),
+        e.medal = match i {
+            0 => Some("🥇".into()),
+            1 => Some("🥈".into()),
+            2 => Some("🥉".into()),
+            _ => None,
         };
     }
 
-    Json(entries)
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::submission::AppState;
+    use crate::testutil::{app_state, temp_dir};
+
+    async fn board(
+        app: &AppState,
+        season: Option<&str>,
+        view: &str,
+    ) -> Result<Vec<LeaderboardEntry>, (StatusCode, String)> {
+        let q = LeaderboardQuery { season: season.map(Into::into), view: Some(view.into()) };
+        let res = get_leaderboard(
+            State(app.sessions.clone()),
+            State(app.tiers.clone()),
+            State(app.seasons.clone()),
+            State(app.rules.clone()),
+            Query(q),
+        );
+        res.await.map(|Json(entries)| entries)
+    }
+
+    #[tokio::test]
+    async fn decayed_view_is_all_time_only() {
+        let dir = temp_dir("leaderboard-decayed");
+        let path = dir.join("rules.toml");
+        let rules = "min_mu_level = 15\n[decay]\nhalf_life_days = 7\n[mined]\n15 = 15\n[witness]\n15 = 7\n";
+        std::fs::write(&path, rules).unwrap();
+        let app = AppState { rules: Arc::new(RulesHandle::open(Some(path)).unwrap()), ..app_state(&dir) };
+
+        let (status, message) = board(&app, Some("s1"), "decayed").await.unwrap_err();
+        assert_eq!((status, message.as_str()), (StatusCode::BAD_REQUEST, "the decayed view is all-time only"));
+        assert!(board(&app, None, "decayed").await.unwrap().is_empty());
+        assert_eq!(board(&app, None, "hot").await.unwrap_err().0, StatusCode::BAD_REQUEST);
+
+        // without a decay rule there is nothing to weight by
+        let plain = app_state(&dir);
+        assert_eq!(board(&plain, None, "decayed").await.unwrap_err().1, "no decay rule is configured");
+    }
 }
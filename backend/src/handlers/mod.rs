@@ -5,3 +5,14 @@ pub mod events;
 pub mod submit_mine;
 pub mod submit_witness;
 pub mod health;
+pub mod audit;
+pub mod reorg;
+pub mod rules;
+pub mod seasons;
+pub mod achievements;
+pub mod guilds;
+pub mod state_root;
+#[cfg(feature = "proofs")]
+pub mod proofs;
+#[cfg(feature = "proofs")]
+pub mod witness_roots;
@@ -0,0 +1,18 @@
+// backend/src/handlers/proofs.rs
+
+use std::sync::Arc;
+
+use axum::{extract::{Path, State}, http::StatusCode, Json};
+
+use crate::store::batch::{BatchingStore, InclusionProof};
+
+/// Merkle inclusion proof for one logged submission, by its `seq`.
+pub async fn get_proof(
+    State(batches): State<Arc<BatchingStore>>,
+    Path(seq): Path<u64>,
+) -> Result<Json<InclusionProof>, (StatusCode, String)> {
+    batches
+        .proof_for(seq)
+        .map(Json)
+        .ok_or((StatusCode::NOT_FOUND, "record not in a sealed batch yet".into()))
+}
@@ -0,0 +1,238 @@
+// backend/src/handlers/reorg.rs
+// Indexer/admin hook for chain reorgs: scores earned on orphaned blocks are
+// taken back, and every cancelled event gets a revert record in the log.
+
+use axum::{extract::State, http::{HeaderMap, StatusCode}, Json};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use crate::engine::tier::TierTable;
+use crate::handlers::health::HealthState;
+use crate::handlers::submission::{
+    decode_pubkey_from_hex, note_tier_change, store_error, AppState, Submission,
+};
+use crate::engine::guild::GuildRollback;
+use crate::state::{root::log_state_root, Guilds, Sessions};
+use crate::store::{schema::SCHEMA_VERSION, StoreError, SubmissionStore};
+
+#[derive(Deserialize)]
+pub struct ReorgReq {
+    /// Every block at or above this height was orphaned.
+    pub orphaned_from: u64,
+}
+
+/// Why `orphan_blocks` stopped. Reverts appended before the failure stay
+/// in effect; calling again resumes after them.
+#[derive(Debug)]
+pub enum ReorgError {
+    Store(StoreError),
+    /// The wallet's score no longer covers what the event earned, so it was
+    /// left unreverted and nothing was logged for it.
+    Uncovered { seq: Option<u64>, wallet: String, points: u32, score: u32 },
+}
+
+impl std::fmt::Display for ReorgError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Store(e) => write!(f, "{e}"),
+            Self::Uncovered { seq, wallet, points, score } => write!(
+                f,
+                "cannot revert record {} of {wallet}: it earned {points} points but the score is {score}",
+                seq.map_or("?".into(), |n| n.to_string())
+            ),
+        }
+    }
+}
+
+impl From<StoreError> for ReorgError {
+    fn from(e: StoreError) -> Self { Self::Store(e) }
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ReorgReport {
+    pub orphaned_from: u64,
+    pub reverted: usize,       // revert records written
+    pub points_removed: u64,
+    pub wallets: usize,
+}
+
+pub async fn post_reorg(
+    State((app, h)): State<(AppState, HealthState)>,
+    headers: HeaderMap,
+    Json(req): Json<ReorgReq>,
+) -> Result<Json<ReorgReport>, (StatusCode, String)> {
+    let need = h.admin_token.clone().unwrap_or_default();
+    let got = headers.get("x-admin-token").and_then(|v| v.to_str().ok()).unwrap_or("");
+    if need.is_empty() || need != got {
+        return Err((StatusCode::UNAUTHORIZED, "unauthorized".into()));
+    }
+    if req.orphaned_from == 0 {
+        return Err((StatusCode::BAD_REQUEST, "orphaned_from must be > 0".into()));
+    }
+
+    let report = orphan_blocks(app.store.as_ref(), &app.sessions, &app.guilds, &app.tiers, req.orphaned_from)
+        .map_err(|e| match e {
+            ReorgError::Store(e) => store_error(e),
+            e @ ReorgError::Uncovered { .. } => (StatusCode::CONFLICT, e.to_string()),
+        })?;
+    println!(
+        "⛓️ Reorg from height {}: {} events reverted, {} points removed across {} wallets",
+        report.orphaned_from, report.reverted, report.points_removed, report.wallets
+    );
+    // witness sets of orphaned blocks must not vouch for their replacements
+    #[cfg(feature = "proofs")]
+    {
+        let retired = app.witness_roots.retire_from(req.orphaned_from).map_err(store_error)?;
+        if retired > 0 {
+            println!("🌿 Retired {retired} witness roots from height {}", req.orphaned_from);
+        }
+    }
+    Ok(Json(report))
+}
+
+/// Reverts every logged event on a block at or above `from_height` that is
+/// not already reverted, newest first. Each revert is appended before the
+/// session is rolled back, so a failed append leaves both untouched; calling
+/// again after a failure picks up where it stopped. An event whose points
+/// the session can no longer cover stops the reorg before it is logged.
+///
+/// Legacy records without a `seq` cannot be referenced and are left alone,
+/// as are height-0 witness reports (block unknown).
+pub fn orphan_blocks(
+    store: &dyn SubmissionStore,
+    sessions: &Sessions,
+    guilds: &Guilds,
+    tiers: &TierTable,
+    from_height: u64,
+) -> Result<ReorgReport, ReorgError> {
+    let mut sessions = sessions.write();
+    let mut guilds = guilds.write();
+
+    let mut already: HashSet<u64> = HashSet::new();
+    let mut orphaned: Vec<Submission> = Vec::new();
+    store.for_each(&mut |s| {
+        if let Some(seq) = s.reverts {
+            already.insert(seq);
+        } else if s.seq.is_some() && s.block_height != 0 && s.block_height >= from_height {
+            orphaned.push(s);
+        }
+    })?;
+    orphaned.retain(|s| !s.seq.is_some_and(|n| already.contains(&n)));
+
+    let mut report = ReorgReport { orphaned_from: from_height, ..Default::default() };
+    let mut wallets = HashSet::new();
+    for orig in orphaned.into_iter().rev() {
+        let is_witness = orig.event_type == "witness";
+        // the session only holds a record if the event actually scored;
+        // unlock records score nothing and are handled below
+        let undo = decode_pubkey_from_hex(&orig.wallet).ok().filter(|_| orig.achievement.is_none()).and_then(|pk| {
+            let rb = sessions.get(&pk)?.rollback_for(orig.block_height, is_witness)?;
+            Some((pk, rb))
+        });
+
+        let points = undo.as_ref().map_or(0, |(_, rb)| rb.points);
+        let before = undo.as_ref().and_then(|(pk, _)| sessions.get(pk)).map_or(0, |s| s.get_score());
+        if before < points {
+            return Err(ReorgError::Uncovered { seq: orig.seq, wallet: orig.wallet, points, score: before });
+        }
+        let revert = Submission {
+            score: points,
+            date_mined: Utc::now(),
+            schema_version: SCHEMA_VERSION,
+            seq: None,
+            prev_hash: None,
+            idempotency_key: None,
+            reverts: orig.seq,
+            bonuses: None,
+            tier_change: note_tier_change(tiers, &orig.wallet, before, before.saturating_sub(points)),
+            ..orig
+        };
+        store.append(&revert)?;
+
+        report.reverted += 1;
+        report.points_removed += revert.score as u64;
+        if let Some((pk, rb)) = undo {
+            let points = rb.points;
+            // checked above, under the same lock
+            assert!(sessions.rollback(&pk, rb), "covered rollback refused");
+            if let Some(name) = &revert.guild {
+                guilds.rollback(name, GuildRollback::credit(points));
+            }
+            wallets.insert(pk);
+        }
+        // unlock records carry the block that triggered them
+        if let Some(id) = &revert.achievement {
+            if let Ok(pk) = decode_pubkey_from_hex(&revert.wallet) {
+                if let Some(session) = sessions.get_mut(&pk) {
+                    session.relock(id);
+                }
+            }
+        }
+    }
+    report.wallets = wallets.len();
+    if report.reverted > 0 {
+        log_state_root(sessions.iter());
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::pki::PubKey;
+    use crate::testutil::{app_state, mine, temp_dir, wallet};
+
+    fn score(app: &AppState, wallet: &str) -> u32 {
+        let pk: PubKey = decode_pubkey_from_hex(wallet).unwrap();
+        app.sessions.read().get(&pk).map_or(0, |s| s.get_score())
+    }
+
+    fn orphan(app: &AppState, from_height: u64) -> ReorgReport {
+        orphan_blocks(app.store.as_ref(), &app.sessions, &app.guilds, &app.tiers, from_height).unwrap()
+    }
+
+    #[tokio::test]
+    async fn reorg_takes_back_orphaned_points_once() {
+        let dir = temp_dir("reorg");
+        let app = app_state(&dir);
+        let (a, b) = (wallet('a'), wallet('b'));
+
+        mine(&app, &a, None, 18, 5).await.unwrap();
+        let kept = score(&app, &a);
+        let orphaned = mine(&app, &a, None, 16, 8).await.unwrap();
+        let lost_a = orphaned["points_awarded"].as_u64().unwrap();
+        let lost_b = mine(&app, &b, None, 17, 9).await.unwrap()["points_awarded"].as_u64().unwrap();
+
+        let report = orphan(&app, 8);
+        assert_eq!((report.reverted, report.wallets), (2, 2));
+        assert_eq!(report.points_removed, lost_a + lost_b);
+        assert_eq!(score(&app, &a), kept);
+        assert_eq!(score(&app, &b), 0);
+        // the leaderboard's metadata falls back to the surviving block
+        let pk = decode_pubkey_from_hex(&a).unwrap();
+        assert_eq!(app.sessions.read()[&pk].last_event(None).map(|l| l.block_height), Some(5));
+
+        let reverts: Vec<u64> = app.store.by_wallet(&a).unwrap().iter().filter_map(|s| s.reverts).collect();
+        assert_eq!(reverts, vec![orphaned["seq"].as_u64().unwrap()]);
+
+        // already reverted events are not reverted again
+        assert_eq!(orphan(&app, 8).reverted, 0);
+        assert_eq!(score(&app, &a), kept);
+    }
+
+    #[tokio::test]
+    async fn replacement_block_scores_after_a_reorg() {
+        let dir = temp_dir("reorg-replacement");
+        let app = app_state(&dir);
+        let a = wallet('a');
+
+        mine(&app, &a, None, 18, 8).await.unwrap();
+        assert_eq!(mine(&app, &a, None, 18, 8).await.unwrap_err().status, axum::http::StatusCode::CONFLICT);
+
+        orphan(&app, 8);
+        assert_eq!(score(&app, &a), 0);
+        let replacement = mine(&app, &a, None, 16, 8).await.unwrap();
+        assert_eq!(score(&app, &a) as u64, replacement["points_awarded"].as_u64().unwrap());
+    }
+}
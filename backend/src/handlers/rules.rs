@@ -0,0 +1,76 @@
+// backend/src/handlers/rules.rs
+
+use axum::{extract::State, http::{HeaderMap, StatusCode}, Json};
+use std::sync::Arc;
+
+use crate::engine::rules::{RulesHandle, ScoringRules};
+use crate::handlers::health::HealthState;
+use crate::handlers::submission::{store_error, AppState};
+
+fn rules_body(rules: &ScoringRules) -> serde_json::Value {
+    serde_json::json!({ "ok": true, "id": rules.id(), "rules": rules })
+}
+
+/// The scoring table in force.
+pub async fn get_rules(State(rules): State<Arc<RulesHandle>>) -> Json<serde_json::Value> {
+    Json(rules_body(&rules.current()))
+}
+
+/// Re-reads `SCORING_RULES_PATH`. A file that fails validation is refused
+/// and the running rules stay as they were. New rules apply to new events
+/// only; logged scores are never recomputed. The table is added to the rules
+/// history first, so replay can follow its bonus rules.
+pub async fn reload_rules(
+    State((app, h)): State<(AppState, HealthState)>,
+    headers: HeaderMap,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let need = h.admin_token.clone().unwrap_or_default();
+    let got = headers.get("x-admin-token").and_then(|v| v.to_str().ok()).unwrap_or("");
+    if need.is_empty() || need != got {
+        return Err((StatusCode::UNAUTHORIZED, "unauthorized".into()));
+    }
+
+    let rules = app.rules.read_file().map_err(|e| {
+        eprintln!("⚠️  Scoring rules reload refused: {e}");
+        (StatusCode::UNPROCESSABLE_ENTITY, e)
+    })?;
+    // replay needs the table's bonus rules, so it is kept before going live
+    app.rules_history.record(&rules).map_err(store_error)?;
+    let rules = app.rules.replace(rules);
+    println!("📐 Scoring rules reloaded: {}", rules.id());
+    Ok(Json(rules_body(&rules)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::{admin_headers, app_state, health_state, temp_dir};
+
+    #[tokio::test]
+    async fn an_invalid_edit_is_refused_and_the_old_rules_stay() {
+        let dir = temp_dir("rules-reload");
+        let path = dir.join("rules.toml");
+        std::fs::write(&path, "min_mu_level = 15\n[mined]\n15 = 15\n[witness]\n15 = 7\n").unwrap();
+        let app = AppState { rules: Arc::new(RulesHandle::open(Some(path.clone())).unwrap()), ..app_state(&dir) };
+        let running = app.rules.current();
+
+        for edit in [
+            // valid TOML, but points drop from μ 15 to μ 16
+            "min_mu_level = 15\n[mined]\n15 = 15\n16 = 10\n[witness]\n15 = 7\n",
+            "min_mu_level = 15\n[mined\n",
+        ] {
+            std::fs::write(&path, edit).unwrap();
+            let (status, _) = reload_rules(State((app.clone(), health_state())), admin_headers()).await.unwrap_err();
+            assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+            assert_eq!(app.rules.current(), running);
+            assert!(app.rules_history.is_empty());
+        }
+        let Json(shown) = get_rules(State(app.rules.clone())).await;
+        assert_eq!(shown["id"], running.id());
+
+        std::fs::write(&path, "min_mu_level = 16\n[mined]\n16 = 30\n[witness]\n16 = 9\n").unwrap();
+        let Json(reloaded) = reload_rules(State((app.clone(), health_state())), admin_headers()).await.unwrap();
+        assert_ne!(reloaded["id"], running.id());
+        assert_eq!(app.rules.current().min_mu_level, 16);
+    }
+}
@@ -0,0 +1,39 @@
+// backend/src/handlers/seasons.rs
+
+use axum::{extract::State, Json};
+use chrono::Utc;
+use serde::Serialize;
+use std::sync::Arc;
+
+use crate::engine::season::Season;
+use crate::state::seasons::Seasons;
+
+#[derive(Serialize)]
+pub struct SeasonInfo {
+    #[serde(flatten)]
+    pub season: Season,
+    pub status: &'static str, // "upcoming" | "active" | "ended" | "archived"
+}
+
+/// The season calendar with where each season stands.
+pub async fn get_seasons(State(seasons): State<Arc<Seasons>>) -> Json<Vec<SeasonInfo>> {
+    let now = Utc::now();
+    let list = seasons
+        .calendar()
+        .seasons()
+        .iter()
+        .map(|s| {
+            let status = if seasons.is_archived(&s.id) {
+                "archived"
+            } else if s.contains(now) {
+                "active"
+            } else if now < s.starts_at {
+                "upcoming"
+            } else {
+                "ended"
+            };
+            SeasonInfo { season: s.clone(), status }
+        })
+        .collect();
+    Json(list)
+}
@@ -0,0 +1,40 @@
+// backend/src/handlers/state_root.rs
+
+use axum::{extract::State, Json};
+use serde::Serialize;
+
+use crate::handlers::submission::{store_error, AppState};
+use crate::state::root::{session_roots, state_root};
+
+#[derive(Serialize)]
+pub struct SessionRoot {
+    pub wallet: String,
+    pub root: String,
+}
+
+/// Hashes for comparing nodes: two backends that applied the same log up
+/// to `cursor` report the same `root`.
+#[derive(Serialize)]
+pub struct StateRoots {
+    pub root: String,
+    pub cursor: u64,
+    /// Per wallet, in the order the root hashes them.
+    pub sessions: Vec<SessionRoot>,
+}
+
+pub async fn get_state_root(
+    State(app): State<AppState>,
+) -> Result<Json<StateRoots>, (axum::http::StatusCode, String)> {
+    // sessions first, then the cursor, like snapshots
+    let sessions = app.sessions.read();
+    let cursor = app.store.cursor().map_err(store_error)?;
+    let roots = session_roots(sessions.iter());
+    Ok(Json(StateRoots {
+        root: hex::encode(state_root(&roots)),
+        cursor,
+        sessions: roots
+            .iter()
+            .map(|(pk, r)| SessionRoot { wallet: hex::encode(pk.as_bytes()), root: hex::encode(r) })
+            .collect(),
+    }))
+}
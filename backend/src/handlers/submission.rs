@@ -1,64 +1,187 @@
 //backend > src > handlers > submissions.rs
 
-use axum::{extract::{State, Json}, http::HeaderMap};
-use serde::{Deserialize, Serialize};
-use std::{
-    fs::{OpenOptions, create_dir_all, File},
-    io::{Write, BufRead, BufReader},
-    sync::{Arc, RwLock},
+use axum::{
+    extract::{FromRef, State, Json},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
 };
-use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use chrono::{DateTime, Utc};
 use hex;
 
-use crate::state::{pki::PubKey, SESSIONS};
-use crate::engine::kdapp::MueHeroSession;
-use crate::state::types::SuperblockEvent;
+use crate::handlers::achievements::check_event_achievements;
+use crate::handlers::guilds::{credit_guild, guild_of};
+use crate::state::{manager::{CommitError, Episodes}, pki::PubKey, seasons::Seasons, Guilds, Sessions};
+use crate::engine::achievement::AchievementBook;
+use crate::engine::game::Bonus;
+use crate::engine::guild::{GuildCommand, GuildRollback};
+use crate::engine::kdapp::{CommandError, MueCommand, MueHeroSession, Scoring};
+use crate::engine::kdapp::SeasonRef;
+use crate::engine::rules::{RulesHandle, ScoringRules};
+use crate::engine::season::SeasonCalendar;
+use crate::engine::tier::{TierChange, TierTable};
+use crate::state::{root::log_session_root, types::SuperblockEvent};
 use crate::episode::PayloadMetadata;
-use crate::episode::Episode;
+use crate::episode::EpisodeError;
+use crate::store::schema::SubmissionRecord;
+use crate::store::{rules_history::RulesHistory, StoreError, SubmissionStore};
+#[cfg(feature = "proofs")]
+use crate::store::witness_roots::WitnessRoots;
 
 
+/// Current-schema record. Reading goes through `schema::SubmissionRecord`,
+/// which upgrades older versions.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "SubmissionRecord")]
 pub struct Submission {
     pub wallet: String,
     pub score: u32,
     pub mu_level: u8,
     pub block_height: u64,
-    pub date_mined: String,   // ISO-8601 UTC preferred
+    pub date_mined: DateTime<Utc>,
     pub event_type: String,   // "mined" | "witness"
+    pub schema_version: u16,
+    // audit chain, stamped by the store on append (absent on legacy records)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seq: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev_hash: Option<String>,
+    /// Client-chosen `Idempotency-Key` the record was submitted under.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idempotency_key: Option<String>,
+    /// Set on revert records: `seq` of the event a reorg orphaned. The rest
+    /// of the record repeats that event, with `score` = points taken back.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reverts: Option<u64>,
+    /// Tier the wallet left and entered with this record, if it crossed one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tier_change: Option<TierChange>,
+    /// `ScoringRules::id` of the table that computed `score`. Replay awards
+    /// such records exactly their logged score; records without it (written
+    /// before rules ids were logged) are re-scored under the current rules.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rules: Option<String>,
+    /// Season the record counted toward; older records are placed by date.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub season: Option<String>,
+    /// Bonus line items included in `score`; the rest is base points.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bonuses: Option<Vec<Bonus>>,
+    /// On `event_type` "achievement" records: the id unlocked.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub achievement: Option<String>,
+    /// Guild joined or left (`guild_join`/`guild_leave` records), or the
+    /// guild a scored event was credited to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub guild: Option<String>,
+}
+
+/// Event types only the server writes; clients cannot submit them.
+pub const SERVER_EVENT_TYPES: [&str; 3] = ["achievement", "guild_join", "guild_leave"];
+
+pub type SharedState = Arc<dyn SubmissionStore>;
+
+/// Router state for the API. Handlers extract only the parts they use,
+/// e.g. `State<SharedState>` and/or `State<Arc<Sessions>>`.
+#[derive(Clone)]
+pub struct AppState {
+    pub store: SharedState,
+    pub sessions: Arc<Sessions>,
+    pub tiers: Arc<TierTable>,
+    pub rules: Arc<RulesHandle>,
+    /// Every table `rules` has held, for replaying records under their own.
+    pub rules_history: Arc<RulesHistory>,
+    pub seasons: Arc<Seasons>,
+    pub achievements: Arc<AchievementBook>,
+    pub guilds: Arc<Guilds>,
+    /// Trusted roots that witness proofs are checked against.
+    #[cfg(feature = "proofs")]
+    pub witness_roots: Arc<WitnessRoots>,
+}
+
+impl FromRef<AppState> for SharedState {
+    fn from_ref(app: &AppState) -> Self { app.store.clone() }
+}
+
+impl FromRef<AppState> for Arc<Sessions> {
+    fn from_ref(app: &AppState) -> Self { app.sessions.clone() }
 }
 
-pub type SharedState = Arc<RwLock<Vec<Submission>>>;
+impl FromRef<AppState> for Arc<TierTable> {
+    fn from_ref(app: &AppState) -> Self { app.tiers.clone() }
+}
+
+impl FromRef<AppState> for Arc<RulesHandle> {
+    fn from_ref(app: &AppState) -> Self { app.rules.clone() }
+}
+
+impl FromRef<AppState> for Arc<Seasons> {
+    fn from_ref(app: &AppState) -> Self { app.seasons.clone() }
+}
+
+impl FromRef<AppState> for Arc<AchievementBook> {
+    fn from_ref(app: &AppState) -> Self { app.achievements.clone() }
+}
+
+impl FromRef<AppState> for Arc<Guilds> {
+    fn from_ref(app: &AppState) -> Self { app.guilds.clone() }
+}
+
+#[cfg(feature = "proofs")]
+impl FromRef<AppState> for Arc<WitnessRoots> {
+    fn from_ref(app: &AppState) -> Self { app.witness_roots.clone() }
+}
+
+/// Logs a tier change for the operator and hands it back for the record.
+pub fn note_tier_change(tiers: &TierTable, wallet: &str, before: u32, after: u32) -> Option<TierChange> {
+    let change = tiers.change(before, after)?;
+    println!("🏅 {wallet}: {} → {}", change.from, change.to);
+    Some(change)
+}
 
 pub async fn handle_submission(
-    State(state): State<SharedState>,
+    State(app): State<AppState>,
     headers: HeaderMap,
-    Json(mut payload): Json<Submission>,
-) -> Result<Json<serde_json::Value>, (axum::http::StatusCode, String)> {
+    Json(mut record): Json<SubmissionRecord>,
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let AppState { store: state, sessions, tiers, rules, seasons, achievements, guilds, .. } = app;
     // --- auth ---
     let expected = std::env::var("MUE_SECRET").unwrap_or_default();
     let got = headers.get("X-MUE-KEY").and_then(|h| h.to_str().ok()).unwrap_or("");
     if expected.is_empty() || got != expected {
-        return Err((axum::http::StatusCode::UNAUTHORIZED, "bad secret".into()));
+        return Err((axum::http::StatusCode::UNAUTHORIZED, "bad secret".to_string()).into());
     }
 
     // --- sanity tweaks for MVP ---
-    if payload.mu_level < 15 {
-        return Ok(Json(serde_json::json!({"status":"ignored"})));
+    let rules = rules.current();
+    if record.mu_level < rules.min_mu_level {
+        return Err(command_error(EpisodeError::CommandError(CommandError::BelowThreshold {
+            mu_level: record.mu_level,
+            min_mu_level: rules.min_mu_level,
+        })));
+    }
+    if record.date_mined.trim().is_empty() {
+        record.date_mined = Utc::now().to_rfc3339();
     }
-    if payload.date_mined.trim().is_empty() {
-        payload.date_mined = Utc::now().to_rfc3339();
+    // accepts the simulator's older date format and any older schema_version
+    let mut payload = Submission::try_from(record)
+        .map_err(|e| (axum::http::StatusCode::BAD_REQUEST, e))?;
+    payload.idempotency_key = idempotency_key(&headers)?;
+    // server-written fields
+    payload.reverts = None;
+    payload.tier_change = None;
+    // `score`, `rules` and `bonuses` are the engine's, set when it commits
+    payload.rules = None;
+    payload.bonuses = None;
+    payload.achievement = None;
+    payload.guild = None;
+    if SERVER_EVENT_TYPES.contains(&payload.event_type.as_str()) {
+        return Err((axum::http::StatusCode::BAD_REQUEST, format!("{} records are server-written", payload.event_type)).into());
     }
 
     // --- feed into kdapp session ---
-    let pubkey_bytes = hex::decode(&payload.wallet).map_err(|_| {
-        (axum::http::StatusCode::BAD_REQUEST, "invalid wallet hex".to_string())
-    })?;
-    if pubkey_bytes.len() < 32 {
-        return Err((axum::http::StatusCode::BAD_REQUEST, "wallet too short".to_string()));
-    }
-    let mut pk_arr = [0u8; 32];
-    pk_arr.copy_from_slice(&pubkey_bytes[..32]);
-    let pubkey = PubKey::new(pk_arr);
+    let pubkey = decode_pubkey_from_hex(&payload.wallet)?;
 
     let event = SuperblockEvent {
         wallet: payload.wallet.clone(),
@@ -70,41 +193,429 @@ pub async fn handle_submission(
         block_height: payload.block_height,
     };
 
+    // --- persist while holding the session lock so snapshots see both or neither ---
     {
-        let mut sessions = SESSIONS.write().unwrap();
-        let session = sessions.entry(pubkey.clone()).or_insert_with(|| {
-            MueHeroSession::initialize(vec![pubkey.clone()], &PayloadMetadata { accepting_time: 0 })
-        });
-        let _ = session.execute(&event, Some(pubkey.clone()), &PayloadMetadata { accepting_time: 0 });
+        let mut sessions = sessions.write();
+        let mut guilds = guilds.write();
+        let retry = find_retry(
+            state.as_ref(),
+            payload.idempotency_key.as_deref(),
+            &payload.wallet,
+            payload.block_height,
+            &payload.event_type,
+        )?;
+        if retry.is_some() {
+            return Ok(Json(serde_json::json!({"status":"ok","idempotent_replay":true})));
+        }
+        if event.is_witness {
+            check_witness(&sessions, &pubkey, payload.block_height).map_err(command_error)?;
+        }
+
+        let meta = PayloadMetadata { accepting_time: payload.date_mined.timestamp().max(0) as u64 };
+        let before = sessions.get(&pubkey).map_or(0, |s| s.get_score());
+        let season = seasons.season_ref(payload.date_mined);
+        payload.season = season.as_ref().map(|s| s.id.clone());
+        payload.guild = guild_of(&guilds, &pubkey);
+        let rules_id = rules.id();
+        let cmd = MueCommand { event, scoring: Scoring::Rules(rules), season };
+        let points = sessions
+            .commit(&pubkey, std::slice::from_ref(&pubkey), &cmd, Some(pubkey.clone()), &meta, |rollback| {
+                // log what the engine awarded, as /submit/mine does, so
+                // replay reproduces it under the rules that computed it
+                payload.score = rollback.points;
+                payload.rules = Some(rules_id);
+                payload.bonuses = (!rollback.bonuses.is_empty()).then(|| rollback.bonuses.clone());
+                payload.tier_change =
+                    note_tier_change(&tiers, &payload.wallet, before, before + rollback.points);
+                state.append(&payload).map(|_| rollback.points)
+            })
+            .map_err(commit_error)?;
+        credit_guild(&mut guilds, payload.guild.as_deref(), &pubkey, points, &meta);
+        check_event_achievements(state.as_ref(), &mut sessions, &achievements, &pubkey, (&payload).into());
+        if let Some(session) = sessions.get(&pubkey) {
+            log_session_root(&pubkey, session);
+        }
     }
 
-    // --- append to memory + JSONL ---
-    {
-        let mut vec = state.write().unwrap();
-        vec.push(payload.clone());
+    Ok(Json(serde_json::json!({"status":"ok"})))
+}
+
+/// Wallets are hex-encoded 32-byte pubkeys (optionally `0x`-prefixed).
+pub fn decode_pubkey_from_hex(s: &str) -> Result<PubKey, (axum::http::StatusCode, String)> {
+    let h = s.strip_prefix("0x").unwrap_or(s);
+    let bytes = hex::decode(h).map_err(|_| (axum::http::StatusCode::BAD_REQUEST, "invalid wallet hex".to_string()))?;
+    if bytes.len() < 32 {
+        return Err((axum::http::StatusCode::BAD_REQUEST, "wallet too short".to_string()));
+    }
+    let mut pk = [0u8; 32];
+    pk.copy_from_slice(&bytes[..32]);
+    Ok(PubKey::new(pk))
+}
+
+pub fn store_error(e: StoreError) -> (axum::http::StatusCode, String) {
+    (axum::http::StatusCode::INTERNAL_SERVER_ERROR, format!("store error: {e}"))
+}
+
+/// Error reply of the submit endpoints:
+/// `{"ok": false, "error": <code>, "message": <text>, ...details}`.
+#[derive(Debug)]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub body: serde_json::Value,
+}
+
+impl ApiError {
+    pub fn new(status: StatusCode, code: &str, message: impl Into<String>) -> Self {
+        let body = serde_json::json!({ "ok": false, "error": code, "message": message.into() });
+        Self { status, body }
     }
-    create_dir_all("logs").ok();
-    if let Ok(mut f) = OpenOptions::new().create(true).append(true).open("logs/submissions.jsonl") {
-        let _ = writeln!(f, "{}", serde_json::to_string(&payload).unwrap());
+
+    fn with(mut self, key: &str, value: impl Into<serde_json::Value>) -> Self {
+        self.body[key] = value.into();
+        self
     }
+}
 
-    Ok(Json(serde_json::json!({"status":"ok"})))
+/// Plain `(status, message)` errors get a code derived from the status.
+impl From<(StatusCode, String)> for ApiError {
+    fn from((status, message): (StatusCode, String)) -> Self {
+        let code = status
+            .canonical_reason()
+            .unwrap_or("error")
+            .to_lowercase()
+            .replace(' ', "_");
+        Self::new(status, &code, message)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.status, Json(self.body)).into_response()
+    }
 }
 
-// -------- JSONL loader for preload on startup --------
-pub fn load_submissions_from_jsonl(path: &str) -> Vec<Submission> {
-    let file = match File::open(path) {
-        Ok(f) => f,
-        Err(_) => return Vec::new(),
+pub fn command_error(e: EpisodeError<CommandError>) -> ApiError {
+    let cmd = match e {
+        EpisodeError::CommandError(cmd) => cmd,
+        EpisodeError::InternalError(msg) => {
+            return ApiError::new(StatusCode::INTERNAL_SERVER_ERROR, "internal_error", msg)
+        }
     };
-    let reader = BufReader::new(file);
-    let mut out = Vec::new();
-    for line in reader.lines().flatten() {
-        let line = line.trim();
-        if line.is_empty() { continue; }
-        if let Ok(s) = serde_json::from_str::<Submission>(line) {
-            out.push(s);
+    let message = cmd.to_string();
+    match cmd {
+        CommandError::BelowThreshold { mu_level, min_mu_level } => {
+            ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, "below_threshold", message)
+                .with("mu_level", mu_level)
+                .with("min_mu_level", min_mu_level)
+        }
+        CommandError::DuplicateBlock { block_height } => {
+            ApiError::new(StatusCode::CONFLICT, "duplicate_block", message)
+                .with("block_height", block_height)
         }
+        CommandError::UnauthorizedSigner => {
+            ApiError::new(StatusCode::FORBIDDEN, "unauthorized_signer", message)
+        }
+        CommandError::WitnessWithoutMinedBlock { block_height } => {
+            ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, "witness_without_mined_block", message)
+                .with("block_height", block_height)
+        }
+        CommandError::SelfWitness { block_height } => {
+            ApiError::new(StatusCode::UNPROCESSABLE_ENTITY, "self_witness", message)
+                .with("block_height", block_height)
+        }
+        CommandError::WitnessCapReached { block_height, cap } => {
+            ApiError::new(StatusCode::CONFLICT, "witness_cap_reached", message)
+                .with("block_height", block_height)
+                .with("cap", cap)
+        }
+        CommandError::SeasonClosed => ApiError::new(StatusCode::CONFLICT, "season_closed", message),
+        CommandError::ScoreOverflow => ApiError::new(StatusCode::CONFLICT, "score_overflow", message),
+    }
+}
+
+pub fn commit_error(e: CommitError<CommandError, StoreError>) -> ApiError {
+    match e {
+        CommitError::Command(e) => command_error(e),
+        CommitError::Persist(e) => store_error(e).into(),
+    }
+}
+
+// -------- idempotent retries --------
+
+/// The `Idempotency-Key` header, if the client sent one.
+pub fn idempotency_key(headers: &HeaderMap) -> Result<Option<String>, (axum::http::StatusCode, String)> {
+    let Some(value) = headers.get("Idempotency-Key") else { return Ok(None) };
+    match value.to_str().map(str::trim) {
+        Ok(key) if !key.is_empty() && key.len() <= 255 => Ok(Some(key.to_string())),
+        _ => Err((axum::http::StatusCode::BAD_REQUEST, "invalid Idempotency-Key".into())),
+    }
+}
+
+/// The record already stored under `key` when this request is a retry of it.
+/// Reusing a key for a different block is a client bug and is refused, and
+/// so is retrying an original a reorg has since reverted: its points are
+/// gone, so echoing them back would be wrong.
+/// Call with the session lock held so the answer cannot go stale.
+pub fn find_retry(
+    store: &dyn SubmissionStore,
+    key: Option<&str>,
+    wallet: &str,
+    block_height: u64,
+    event_type: &str,
+) -> Result<Option<Submission>, ApiError> {
+    let Some(key) = key else { return Ok(None) };
+    match store.by_idempotency_key(key).map_err(store_error)? {
+        Some(orig)
+            if orig.wallet == wallet
+                && orig.block_height == block_height
+                && orig.event_type == event_type =>
+        {
+            // reverts keep the original's wallet, so its history has them
+            let reverted = orig.seq.is_some()
+                && store.by_wallet(wallet).map_err(store_error)?.iter().any(|s| s.reverts == orig.seq);
+            if reverted {
+                return Err(ApiError::new(
+                    axum::http::StatusCode::CONFLICT,
+                    "reverted",
+                    "the submission under this Idempotency-Key was reverted by a reorg",
+                )
+                .with("seq", orig.seq));
+            }
+            Ok(Some(orig))
+        }
+        Some(_) => Err((
+            axum::http::StatusCode::UNPROCESSABLE_ENTITY,
+            "Idempotency-Key was already used for a different submission".to_string(),
+        )
+            .into()),
+        None => Ok(None),
+    }
+}
+
+// -------- witness checks --------
+
+/// Witnesses per block when `MAX_WITNESSES_PER_BLOCK` is unset.
+const DEFAULT_WITNESS_CAP: usize = 10;
+
+/// `MAX_WITNESSES_PER_BLOCK`, at least 1.
+pub fn witness_cap() -> usize {
+    std::env::var("MAX_WITNESSES_PER_BLOCK")
+        .ok()
+        .and_then(|v| v.trim().parse().ok())
+        .filter(|&n| n > 0)
+        .unwrap_or(DEFAULT_WITNESS_CAP)
+}
+
+/// A witness must name a block another wallet has a live mined record for,
+/// and the block must have room under the witness cap. Sessions are per
+/// wallet, so this looks across them; call with the session lock held.
+/// Replay skips it: the log only holds witnesses that passed it.
+pub fn check_witness(
+    sessions: &Episodes<'_, MueHeroSession, PubKey>,
+    witness: &PubKey,
+    block_height: u64,
+) -> Result<(), EpisodeError<CommandError>> {
+    let refuse = |e| Err(EpisodeError::CommandError(e));
+    if sessions.get(witness).is_some_and(|s| s.has_scored(block_height, false)) {
+        return refuse(CommandError::SelfWitness { block_height });
+    }
+    if block_height == 0 || !sessions.iter().any(|(_, s)| s.has_scored(block_height, false)) {
+        return refuse(CommandError::WitnessWithoutMinedBlock { block_height });
+    }
+    if sessions.get(witness).is_some_and(|s| s.has_scored(block_height, true)) {
+        return Ok(()); // a repeat: the engine answers `duplicate_block`
+    }
+    let cap = witness_cap();
+    if sessions.iter().filter(|(_, s)| s.has_scored(block_height, true)).count() >= cap {
+        return refuse(CommandError::WitnessCapReached { block_height, cap });
+    }
+    Ok(())
+}
+
+// -------- session replay on startup --------
+
+/// Outcome of feeding logged submissions back through the engine.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ReplayReport {
+    pub replayed: usize,
+    pub skipped: usize,  // below μ threshold or wallet not a hex pubkey
+    pub rejected: usize, // engine refused the command (e.g. a duplicate block) or a revert
+    pub reverted: usize, // revert records applied (reorged blocks)
+}
+
+/// Rebuilds `sessions` by re-executing every record stored after `cursor`
+/// (0 for the whole log), so `/leaderboard` matches `/events` after a restart.
+/// Records without a `rules` id are scored under `rules`; records with one
+/// award their logged points, with the bonus state following that table's
+/// bonus rules from `history` (or `rules`' if it was never recorded).
+/// Records without a `season` are placed by date in `calendar`. Guild membership and credits
+/// are rebuilt into `guilds` along the way.
+pub fn replay_into_sessions(
+    store: &dyn SubmissionStore,
+    sessions: &Sessions,
+    guilds: &Guilds,
+    rules: &Arc<ScoringRules>,
+    history: &RulesHistory,
+    calendar: &SeasonCalendar,
+    cursor: u64,
+) -> Result<ReplayReport, StoreError> {
+    let mut report = ReplayReport::default();
+    let mut sessions = sessions.write();
+    let mut guilds = guilds.write();
+
+    store.for_each_from(cursor, &mut |s| {
+        if let Some(name) = s.guild.as_ref().filter(|_| s.event_type.starts_with("guild_")) {
+            let Ok(pk) = decode_pubkey_from_hex(&s.wallet) else { report.skipped += 1; return };
+            let cmd = match s.event_type.as_str() {
+                "guild_join" if guilds.get(name).is_none() => GuildCommand::Found { member: pk.clone() },
+                "guild_join" => GuildCommand::Join { member: pk.clone() },
+                _ => GuildCommand::Leave { member: pk.clone() },
+            };
+            let meta = PayloadMetadata { accepting_time: s.date_mined.timestamp().max(0) as u64 };
+            match guilds.execute(name, std::slice::from_ref(&pk), &cmd, Some(pk.clone()), &meta) {
+                Ok(_) => report.replayed += 1,
+                Err(_) => report.rejected += 1,
+            }
+            return;
+        }
+        // unlocks are restored from their records, not re-derived, so a
+        // changed achievement set cannot grant unlocks nobody logged
+        if let Some(id) = &s.achievement {
+            let session = decode_pubkey_from_hex(&s.wallet).ok().and_then(|pk| sessions.get_mut(&pk));
+            match (session, s.reverts.is_some()) {
+                (Some(session), false) => { session.unlock(id, s.date_mined.timestamp().max(0) as u64); report.replayed += 1; }
+                (Some(session), true) => { session.relock(id); report.reverted += 1; }
+                (None, _) => report.skipped += 1,
+            }
+            return;
+        }
+        if s.reverts.is_some() {
+            let undo = decode_pubkey_from_hex(&s.wallet).ok().and_then(|pk| {
+                let rb = sessions.get(&pk)?.rollback_for(s.block_height, s.event_type == "witness")?;
+                Some((pk, rb))
+            });
+            match undo {
+                Some((pk, rb)) => {
+                    let points = rb.points;
+                    if sessions.rollback(&pk, rb) {
+                        if let Some(name) = &s.guild {
+                            guilds.rollback(name, GuildRollback::credit(points));
+                        }
+                        report.reverted += 1;
+                    } else {
+                        // the session cannot cover it; left as it was
+                        report.rejected += 1;
+                    }
+                }
+                None => report.skipped += 1,
+            }
+            return;
+        }
+        if s.rules.is_none() && s.mu_level < rules.min_mu_level {
+            report.skipped += 1;
+            return;
+        }
+        let pubkey = match decode_pubkey_from_hex(&s.wallet) {
+            Ok(pk) => pk,
+            Err(_) => { report.skipped += 1; return; }
+        };
+
+        let event = SuperblockEvent {
+            wallet: s.wallet.clone(),
+            mu_level: s.mu_level,
+            is_witness: s.event_type == "witness",
+            merkle_root: None,
+            proof: None,
+            witness_index: None,
+            block_height: s.block_height,
+        };
+
+        let accepting_time = s.date_mined.timestamp().max(0) as u64;
+        let meta = PayloadMetadata { accepting_time };
+
+        let scoring = match &s.rules {
+            Some(id) => {
+                let bonuses = s.bonuses.clone().unwrap_or_default();
+                let bonus_points: u32 = bonuses.iter().map(Bonus::points).sum();
+                Scoring::Logged {
+                    points: s.score.saturating_sub(bonus_points),
+                    bonuses,
+                    bonus_rules: history.bonus_rules(id).unwrap_or_else(|| rules.bonus_rules()),
+                }
+            }
+            None => Scoring::Rules(rules.clone()),
+        };
+        // replay re-applies what was accepted, archived season or not
+        let season = s
+            .season
+            .clone()
+            .or_else(|| calendar.at(s.date_mined).map(|se| se.id.clone()))
+            .map(|id| SeasonRef { id, closed: false });
+        let cmd = MueCommand { event, scoring, season };
+        match sessions.execute(&pubkey, std::slice::from_ref(&pubkey), &cmd, Some(pubkey.clone()), &meta) {
+            Ok(rb) => {
+                credit_guild(&mut guilds, s.guild.as_deref(), &pubkey, rb.points, &meta);
+                report.replayed += 1;
+            }
+            Err(_) => report.rejected += 1,
+        }
+    })?;
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::season::SeasonCalendar;
+    use crate::testutil::{app_state, mined, submit, temp_dir, wallet};
+
+    #[test]
+    fn replay_rebuilds_scores_from_a_log_written_before_a_restart() {
+        let dir = temp_dir("submit-replay");
+        let app = app_state(&dir);
+        let rules = app.rules.current();
+        let (a, b) = (wallet('a'), wallet('b'));
+        let mut low = mined(&a, 4, 1);
+        low.mu_level = rules.min_mu_level - 1;
+        let mut witnessed = mined(&b, 3, 0);
+        witnessed.event_type = "witness".into();
+        for sub in [mined(&a, 3, 0), witnessed, low, mined("not-hex", 5, 0), mined(&a, 3, 0)] {
+            app.store.append(&sub).unwrap();
+        }
+
+        let calendar = SeasonCalendar::default();
+        let report =
+            replay_into_sessions(app.store.as_ref(), &app.sessions, &app.guilds, &rules, &app.rules_history, &calendar, 0)
+                .unwrap();
+        assert_eq!((report.replayed, report.skipped, report.rejected, report.reverted), (2, 2, 1, 0));
+        let score = |w: &str| app.sessions.read()[&decode_pubkey_from_hex(w).unwrap()].get_score();
+        assert_eq!(score(&a), rules.points(18, false));
+        assert_eq!(score(&b), rules.points(18, true));
+    }
+
+    #[tokio::test]
+    async fn legacy_submit_logs_the_engines_points_and_rules() {
+        let dir = temp_dir("submit-legacy");
+        let app = app_state(&dir);
+        let a = wallet('a');
+        let record = serde_json::json!({
+            "wallet": a, "score": 9999, "mu_level": 18, "block_height": 5, "event_type": "mined"
+        });
+        assert_eq!(submit(&app, record).await.unwrap()["status"], "ok");
+
+        let rules = app.rules.current();
+        let logged = app.store.by_wallet(&a).unwrap();
+        assert_eq!(logged[0].score, rules.points(18, false));
+        assert_eq!(logged[0].rules, Some(rules.id()));
+        let live = app.sessions.read().values().next().unwrap().get_score();
+        assert_eq!(live, logged[0].score);
+
+        // replay awards the logged points, not the client's
+        let (sessions, guilds) = (Sessions::new(), Guilds::new());
+        let calendar = SeasonCalendar::default();
+        replay_into_sessions(app.store.as_ref(), &sessions, &guilds, &rules, &app.rules_history, &calendar, 0)
+            .unwrap();
+        assert_eq!(sessions.read().values().next().unwrap().get_score(), live);
     }
-    out
 }
@@ -3,14 +3,17 @@
 use axum::{extract::{State, Json}, http::HeaderMap};
 use chrono::Utc;
 use serde::Deserialize;
-use std::{fs::OpenOptions, io::Write};
-use hex;
 
-use crate::handlers::submission::{Submission, SharedState};
-use crate::state::{SESSIONS, pki::PubKey};
-use crate::engine::kdapp::MueHeroSession;
-use crate::state::types::SuperblockEvent;
-use crate::episode::{Episode, PayloadMetadata};
+use crate::handlers::submission::{
+    commit_error, decode_pubkey_from_hex, find_retry, idempotency_key, note_tier_change, ApiError, AppState, Submission,
+};
+use crate::engine::game::Bonus;
+use crate::handlers::achievements::check_event_achievements;
+use crate::handlers::guilds::{credit_guild, guild_of};
+use crate::engine::kdapp::{MueCommand, Scoring};
+use crate::state::{root::log_session_root, types::SuperblockEvent};
+use crate::episode::PayloadMetadata;
+use crate::store::schema::SCHEMA_VERSION;
 
 #[derive(Deserialize)]
 #[serde(deny_unknown_fields)]
@@ -20,31 +23,21 @@ pub struct MineReq {
     pub block_height: u64,
 }
 
-fn decode_pubkey_from_hex(s: &str) -> Result<PubKey, (axum::http::StatusCode, String)> {
-    let h = s.strip_prefix("0x").unwrap_or(s);
-    let bytes = hex::decode(h).map_err(|_| (axum::http::StatusCode::BAD_REQUEST, "invalid wallet hex".to_string()))?;
-    if bytes.len() < 32 {
-        return Err((axum::http::StatusCode::BAD_REQUEST, "wallet too short".to_string()));
-    }
-    let mut pk = [0u8; 32];
-    pk.copy_from_slice(&bytes[..32]);
-    Ok(PubKey::new(pk))
-}
-
 pub async fn submit_mine(
-    State(state): State<SharedState>,
+    State(app): State<AppState>,
     headers: HeaderMap,
     Json(req): Json<MineReq>,
-) -> Result<Json<serde_json::Value>, (axum::http::StatusCode, String)> {
+) -> Result<Json<serde_json::Value>, ApiError> {
+    let AppState { store: state, sessions, tiers, rules, seasons, achievements, guilds, .. } = app;
     // auth
     let expected = std::env::var("MUE_SECRET").unwrap_or_default();
     let got = headers.get("X-MUE-KEY").and_then(|h| h.to_str().ok()).unwrap_or("");
     if expected.is_empty() || got != expected {
-        return Err((axum::http::StatusCode::UNAUTHORIZED, "bad secret".into()));
+        return Err((axum::http::StatusCode::UNAUTHORIZED, "bad secret".to_string()).into());
     }
 
     if !(1..=64).contains(&req.mu_level) {
-        return Err((axum::http::StatusCode::BAD_REQUEST, "invalid mu_level".into()));
+        return Err((axum::http::StatusCode::BAD_REQUEST, "invalid mu_level".to_string()).into());
     }
 
     // canonical identity for sessions (PubKey)
@@ -61,32 +54,149 @@ pub async fn submit_mine(
         block_height: req.block_height,
     };
 
-    // engine/session update → get awarded points (delta)
-    let meta = PayloadMetadata { accepting_time: Utc::now().timestamp() as u64 };
-    let delta = {
-        let mut sessions = SESSIONS.write().unwrap();
-        let session = sessions.entry(pubkey.clone()).or_insert_with(|| {
-            MueHeroSession::initialize(vec![pubkey.clone()], &meta)
-        });
-        session.execute(&event, Some(pubkey.clone()), &meta).unwrap_or(0)
-    };
+    // engine/session update → get awarded points (delta), then persist while
+    // still holding the session lock so snapshots see both or neither
+    let key = idempotency_key(&headers)?;
+    let (delta, bonuses, link, tier_change, unlocked) = {
+        let mut sessions = sessions.write();
+        let mut guilds = guilds.write();
+        // a retried request gets the original answer instead of scoring again
+        if let Some(orig) = find_retry(state.as_ref(), key.as_deref(), &req.wallet, req.block_height, "mined")? {
+            return Ok(Json(serde_json::json!({
+                "ok": true, "points_awarded": orig.score, "bonuses": orig.bonuses.unwrap_or_default(),
+                "seq": orig.seq, "idempotent_replay": true
+            })));
+        }
 
-    // append to memory + JSONL (log the delta so Events can show per-row points)
-    let payload = Submission {
-        wallet: req.wallet.clone(),
-        score: delta, // <-- if Submission.score is i32, change to `delta as i32`
-        mu_level: req.mu_level,
-        block_height: req.block_height,
-        date_mined: Utc::now().to_rfc3339(),
-        event_type: "mined".into(),
+        let before = sessions.get(&pubkey).map_or(0, |s| s.get_score());
+        let rules = rules.current();
+        let rules_id = rules.id();
+        // the record's date is what replay judges the event at
+        let now = Utc::now();
+        let meta = PayloadMetadata { accepting_time: now.timestamp().max(0) as u64 };
+        let season = seasons.season_ref(now);
+        let season_id = season.as_ref().map(|s| s.id.clone());
+        let guild = guild_of(&guilds, &pubkey);
+        let cmd = MueCommand { event, scoring: Scoring::Rules(rules), season };
+        let (delta, bonuses, link, payload) = sessions
+            .commit(&pubkey, std::slice::from_ref(&pubkey), &cmd, Some(pubkey.clone()), &meta, |rollback| {
+                // persist (log the delta so Events can show per-row points)
+                let payload = Submission {
+                    wallet: req.wallet.clone(),
+                    score: rollback.points,
+                    mu_level: req.mu_level,
+                    block_height: req.block_height,
+                    date_mined: now,
+                    event_type: "mined".into(),
+                    schema_version: SCHEMA_VERSION,
+                    seq: None,
+                    prev_hash: None,
+                    idempotency_key: key.clone(),
+                    reverts: None,
+                    rules: Some(rules_id.clone()),
+                    season: season_id.clone(),
+                    tier_change: note_tier_change(&tiers, &req.wallet, before, before + rollback.points),
+                    bonuses: (!rollback.bonuses.is_empty()).then(|| rollback.bonuses.clone()),
+                    achievement: None,
+                    guild: guild.clone(),
+                };
+                state
+                    .append(&payload)
+                    .map(|link| (rollback.points, rollback.bonuses.clone(), link, payload))
+            })
+            .map_err(commit_error)?;
+        credit_guild(&mut guilds, guild.as_deref(), &pubkey, delta, &meta);
+        let unlocked =
+            check_event_achievements(state.as_ref(), &mut sessions, &achievements, &pubkey, (&payload).into());
+        if let Some(session) = sessions.get(&pubkey) {
+            log_session_root(&pubkey, session);
+        }
+        (delta, bonuses, link, payload.tier_change, unlocked)
     };
-    {
-        let mut vec = state.write().unwrap();
-        vec.push(payload.clone());
+
+    let tier = sessions.read().get(&pubkey).map(|s| s.tier(&tiers).name.clone());
+    Ok(Json(serde_json::json!({
+        "ok": true, "points_awarded": delta, "base_points": delta - bonuses.iter().map(Bonus::points).sum::<u32>(),
+        "bonuses": bonuses, "seq": link.seq, "tier": tier, "tier_change": tier_change,
+        "achievements_unlocked": unlocked
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::StatusCode;
+
+    use crate::handlers::reorg::orphan_blocks;
+    use crate::testutil::{app_state, mine, temp_dir, wallet};
+
+    #[tokio::test]
+    async fn retry_returns_the_original_points_awarded() {
+        let dir = temp_dir("mine-retry");
+        let app = app_state(&dir);
+        let a = wallet('a');
+
+        let first = mine(&app, &a, Some("k1"), 18, 5).await.unwrap();
+        let awarded = first["points_awarded"].as_u64().unwrap();
+        assert!(awarded > 0);
+        // more scoring in between must not change what the retry reports
+        mine(&app, &a, None, 16, 6).await.unwrap();
+        let score = app.sessions.read().values().next().unwrap().get_score();
+
+        let retry = mine(&app, &a, Some("k1"), 18, 5).await.unwrap();
+        assert_eq!(retry["points_awarded"].as_u64(), Some(awarded));
+        assert_eq!(retry["seq"], first["seq"]);
+        assert_eq!(retry["idempotent_replay"], true);
+
+        assert_eq!(app.sessions.read().values().next().unwrap().get_score(), score);
+        let mined = app.store.by_wallet(&a).unwrap();
+        assert_eq!(mined.iter().filter(|s| s.event_type == "mined").count(), 2);
+    }
+
+    #[tokio::test]
+    async fn reusing_a_key_for_another_block_is_refused() {
+        let dir = temp_dir("mine-key-reuse");
+        let app = app_state(&dir);
+        let a = wallet('a');
+
+        mine(&app, &a, Some("k1"), 18, 5).await.unwrap();
+        let err = mine(&app, &a, Some("k1"), 18, 6).await.unwrap_err();
+        assert_eq!(err.status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(app.store.by_wallet(&a).unwrap().iter().filter(|s| s.block_height == 6).count(), 0);
     }
-    if let Ok(mut f) = OpenOptions::new().create(true).append(true).open("logs/submissions.jsonl") {
-        let _ = writeln!(f, "{}", serde_json::to_string(&payload).unwrap());
+
+    #[tokio::test]
+    async fn retry_of_a_reverted_original_is_refused() {
+        let dir = temp_dir("mine-retry-reverted");
+        let app = app_state(&dir);
+        let a = wallet('a');
+
+        let first = mine(&app, &a, Some("k1"), 18, 5).await.unwrap();
+        orphan_blocks(app.store.as_ref(), &app.sessions, &app.guilds, &app.tiers, 5).unwrap();
+
+        let err = mine(&app, &a, Some("k1"), 18, 5).await.unwrap_err();
+        assert_eq!(err.status, StatusCode::CONFLICT);
+        assert_eq!(err.body["error"], "reverted");
+        assert_eq!(err.body["seq"], first["seq"]);
+        assert_eq!(app.sessions.read().values().next().unwrap().get_score(), 0);
     }
 
-    Ok(Json(serde_json::json!({ "ok": true, "points_awarded": delta })))
+    #[tokio::test]
+    async fn crossing_a_threshold_reports_and_logs_the_tier_change() {
+        let dir = temp_dir("mine-tier-change");
+        let app = app_state(&dir);
+        let a = wallet('a');
+
+        // 0 → 400 points crosses μForged at 250
+        let first = mine(&app, &a, None, 21, 5).await.unwrap();
+        let change = serde_json::json!({ "from": "μScout", "to": "μForged" });
+        assert_eq!(first["tier_change"], change);
+        assert_eq!(first["tier"], "μForged");
+        let logged = app.store.by_wallet(&a).unwrap();
+        let record = logged.iter().find(|s| s.block_height == 5 && s.event_type == "mined").unwrap();
+        assert_eq!(serde_json::to_value(&record.tier_change).unwrap(), change);
+
+        let second = mine(&app, &a, None, 15, 6).await.unwrap();
+        assert!(second["tier_change"].is_null());
+        assert_eq!(second["tier"], "μForged");
+    }
 }
@@ -2,20 +2,37 @@
 use axum::{extract::{State, Json}, http::HeaderMap};
 use chrono::Utc;
 use serde::Deserialize;
-use std::{fs::OpenOptions, io::Write};
-use hex;
 
-use crate::handlers::submission::{Submission, SharedState};
-use crate::engine::kdapp::MueHeroSession;
-use crate::state::{SESSIONS, pki::PubKey};
-use crate::state::types::SuperblockEvent;
-use crate::episode::{Episode, PayloadMetadata};
+use crate::handlers::submission::{
+    check_witness, command_error, commit_error, decode_pubkey_from_hex, find_retry, idempotency_key, note_tier_change, ApiError, AppState, Submission,
+};
+use crate::engine::game::Bonus;
+use crate::handlers::achievements::check_event_achievements;
+use crate::handlers::guilds::{credit_guild, guild_of};
+use crate::engine::kdapp::{MueCommand, Scoring};
+#[cfg(feature = "proofs")]
+use crate::engine::merkle::{compute_leaf_from_wallet, merkle_depth, verify_merkle_proof};
+use crate::state::{root::log_session_root, types::SuperblockEvent};
+use crate::episode::PayloadMetadata;
+use crate::store::schema::SCHEMA_VERSION;
 
 #[derive(Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct ProofJson {
-    pub siblings: Vec<String>,   // each "0x" + 64 hex chars
-    pub path: String,            // bitstring e.g. "0101"
+    pub siblings: Vec<String>,   // each "0x" + 128 hex chars (Blake2b-512 nodes), leaf level first
+    pub path: String,            // one bit per tree level, leaf level first; "1" = the node is on the right
+    /// The witness-set root the proof leads to, "0x" + 128 hex chars. With
+    /// the `proofs` feature the block's published root is used; a root sent
+    /// here must match it; without the feature it is not stored.
+    #[serde(default)]
+    pub root: Option<String>,
+}
+
+/// A proof in the form `verify_merkle_proof` takes.
+struct DecodedProof {
+    siblings: Vec<[u8; 64]>,
+    index: usize,
+    root: Option<[u8; 64]>,
 }
 
 #[derive(Deserialize)]
@@ -24,88 +41,326 @@ pub struct WitnessReq {
     pub wallet: String,          // hex, same identity as mining
     pub mu_level: u8,
     pub proof: ProofJson,
-    // optional later: pub block_height: Option<u64>,
+    /// Block being witnessed; another wallet must have a mined record for it.
+    pub block_height: u64,
 }
 
-fn is_hex64(s: &str) -> bool {
-    s.len() == 66 && s.starts_with("0x") && s[2..].chars().all(|c| c.is_ascii_hexdigit())
+fn decode_node(s: &str) -> Option<[u8; 64]> {
+    hex::decode(s.strip_prefix("0x")?).ok()?.try_into().ok()
+}
+/// Leaf index from the path: bit i of the index is the node's side at level i.
+fn path_index(path: &str) -> Option<usize> {
+    path.chars().rev().try_fold(0usize, |index, c| match c {
+        '0' => Some(index << 1),
+        '1' => Some(index << 1 | 1),
+        _ => None,
+    })
 }
-fn is_bitstring(s: &str) -> bool { s.chars().all(|c| c == '0' || c == '1') }
 
-fn decode_pubkey_from_hex(s: &str) -> Result<PubKey, (axum::http::StatusCode, String)> {
-    let h = s.strip_prefix("0x").unwrap_or(s);
-    let bytes = hex::decode(h).map_err(|_| (axum::http::StatusCode::BAD_REQUEST, "invalid wallet hex".to_string()))?;
-    if bytes.len() < 32 {
-        return Err((axum::http::StatusCode::BAD_REQUEST, "wallet too short".to_string()));
-    }
-    let mut pk = [0u8; 32];
-    pk.copy_from_slice(&bytes[..32]);
-    Ok(PubKey::new(pk))
+fn decode_proof(proof: &ProofJson) -> Result<DecodedProof, (axum::http::StatusCode, String)> {
+    let bad = |field: &str| (axum::http::StatusCode::BAD_REQUEST, format!("invalid proof.{field}"));
+    let siblings = proof.siblings.iter().map(|h| decode_node(h)).collect::<Option<Vec<_>>>()
+        .filter(|s| s.len() <= 64)
+        .ok_or_else(|| bad("siblings"))?;
+    // a level whose node is promoted has no sibling, so there may be fewer
+    // siblings than path bits
+    let index = path_index(&proof.path)
+        .filter(|_| (siblings.len()..=64).contains(&proof.path.len()))
+        .ok_or_else(|| bad("path"))?;
+    let root = match &proof.root {
+        Some(r) => Some(decode_node(r).ok_or_else(|| bad("root"))?),
+        None => None,
+    };
+    Ok(DecodedProof { siblings, index, root })
 }
 
 pub async fn submit_witness(
-    State(state): State<SharedState>,
+    State(app): State<AppState>,
     headers: HeaderMap,
     Json(req): Json<WitnessReq>,
-) -> Result<Json<serde_json::Value>, (axum::http::StatusCode, String)> {
+) -> Result<Json<serde_json::Value>, ApiError> {
+    #[cfg(feature = "proofs")]
+    let witness_roots = app.witness_roots.clone();
+    let AppState { store: state, sessions, tiers, rules, seasons, achievements, guilds, .. } = app;
     // auth
     let expected = std::env::var("MUE_SECRET").unwrap_or_default();
     let got = headers.get("X-MUE-KEY").and_then(|h| h.to_str().ok()).unwrap_or("");
     if expected.is_empty() || got != expected {
-        return Err((axum::http::StatusCode::UNAUTHORIZED, "bad secret".into()));
+        return Err((axum::http::StatusCode::UNAUTHORIZED, "bad secret".to_string()).into());
     }
 
     // basic validation (keep as is)
     if !(1..=64).contains(&req.mu_level) {
-        return Err((axum::http::StatusCode::BAD_REQUEST, "invalid mu_level".into()));
-    }
-    if req.proof.siblings.is_empty() || req.proof.siblings.len() > 64 || !req.proof.siblings.iter().all(|h| is_hex64(h)) {
-        return Err((axum::http::StatusCode::BAD_REQUEST, "invalid proof.siblings".into()));
-    }
-    if !is_bitstring(&req.proof.path) {
-        return Err((axum::http::StatusCode::BAD_REQUEST, "invalid proof.path".into()));
+        return Err((axum::http::StatusCode::BAD_REQUEST, "invalid mu_level".to_string()).into());
     }
+    let proof = decode_proof(&req.proof)?;
 
     // canonical identity for sessions (PubKey)
     let pubkey = decode_pubkey_from_hex(&req.wallet)?;
 
+    // the wallet must be a leaf of the block's published witness set
+    #[cfg(feature = "proofs")]
+    let proof = {
+        let unprocessable = |code, message: String| {
+            ApiError::new(axum::http::StatusCode::UNPROCESSABLE_ENTITY, code, message)
+        };
+        let Some((root, wallets)) = witness_roots.root_for(req.block_height) else {
+            return Err(unprocessable(
+                "unknown_witness_root",
+                format!("no witness root published for block {}", req.block_height),
+            ));
+        };
+        if proof.root.is_some_and(|r| r != root) {
+            return Err(unprocessable(
+                "witness_root_mismatch",
+                format!("proof.root is not the published root of block {}", req.block_height),
+            ));
+        }
+        let leaf = compute_leaf_from_wallet(&pubkey);
+        if req.proof.path.len() != merkle_depth(wallets)
+            || !verify_merkle_proof(leaf, proof.siblings.clone(), root, proof.index, wallets)
+        {
+            return Err(unprocessable(
+                "invalid_witness_proof",
+                format!("proof does not lead from the wallet to the root of block {}", req.block_height),
+            ));
+        }
+        DecodedProof { root: Some(root), ..proof }
+    };
+    // without a published root to check against, a client root proves nothing
+    #[cfg(not(feature = "proofs"))]
+    let proof = DecodedProof { root: None, ..proof };
+    let block_height = req.block_height;
+
     // self-contained event (includes wallet string for UI/logs)
     let event = SuperblockEvent {
         wallet: req.wallet.clone(), 
         mu_level: req.mu_level,
         is_witness: true,
-        merkle_root: None,          // TODO: wire verification later
-        proof: None,
-        witness_index: None,
-        block_height: 0,            // TODO: real height later
+        merkle_root: proof.root,
+        proof: Some(proof.siblings),
+        witness_index: Some(proof.index),
+        block_height,
     };
 
-    // engine/session update → get awarded points (delta)
-    let meta = PayloadMetadata { accepting_time: Utc::now().timestamp() as u64 };
-    let delta = {
-        let mut sessions = SESSIONS.write().unwrap();
-        let session = sessions.entry(pubkey.clone()).or_insert_with(|| {
-            MueHeroSession::initialize(vec![pubkey.clone()], &meta)
-        });
-        session.execute(&event, Some(pubkey.clone()), &meta).unwrap_or(0)
-    };
+    // engine/session update → get awarded points (delta), then persist while
+    // still holding the session lock so snapshots see both or neither
+    let key = idempotency_key(&headers)?;
+    let (delta, bonuses, link, tier_change, unlocked) = {
+        let mut sessions = sessions.write();
+        let mut guilds = guilds.write();
+        // a retried request gets the original answer instead of scoring again
+        if let Some(orig) = find_retry(state.as_ref(), key.as_deref(), &req.wallet, block_height, "witness")? {
+            return Ok(Json(serde_json::json!({
+                "ok": true, "points_awarded": orig.score, "bonuses": orig.bonuses.unwrap_or_default(),
+                "seq": orig.seq, "idempotent_replay": true
+            })));
+        }
+        check_witness(&sessions, &pubkey, block_height).map_err(command_error)?;
 
-    // append to memory + JSONL (log wallet + delta so Events can show per-row points)
-    let payload = Submission {
-        wallet: req.wallet.clone(),
-        score: delta, // <-- if Submission.score is i32, change to `delta as i32`
-        mu_level: req.mu_level,
-        block_height: 0,
-        date_mined: Utc::now().to_rfc3339(),
-        event_type: "witness".into(),
+        let before = sessions.get(&pubkey).map_or(0, |s| s.get_score());
+        let rules = rules.current();
+        let rules_id = rules.id();
+        // the record's date is what replay judges the event at
+        let now = Utc::now();
+        let meta = PayloadMetadata { accepting_time: now.timestamp().max(0) as u64 };
+        let season = seasons.season_ref(now);
+        let season_id = season.as_ref().map(|s| s.id.clone());
+        let guild = guild_of(&guilds, &pubkey);
+        let cmd = MueCommand { event, scoring: Scoring::Rules(rules), season };
+        let (delta, bonuses, link, payload) = sessions
+            .commit(&pubkey, std::slice::from_ref(&pubkey), &cmd, Some(pubkey.clone()), &meta, |rollback| {
+                // persist (log wallet + delta so Events can show per-row points)
+                let payload = Submission {
+                    wallet: req.wallet.clone(),
+                    score: rollback.points,
+                    mu_level: req.mu_level,
+                    block_height,
+                    date_mined: now,
+                    event_type: "witness".into(),
+                    schema_version: SCHEMA_VERSION,
+                    seq: None,
+                    prev_hash: None,
+                    idempotency_key: key.clone(),
+                    reverts: None,
+                    rules: Some(rules_id.clone()),
+                    season: season_id.clone(),
+                    tier_change: note_tier_change(&tiers, &req.wallet, before, before + rollback.points),
+                    bonuses: (!rollback.bonuses.is_empty()).then(|| rollback.bonuses.clone()),
+                    achievement: None,
+                    guild: guild.clone(),
+                };
+                state
+                    .append(&payload)
+                    .map(|link| (rollback.points, rollback.bonuses.clone(), link, payload))
+            })
+            .map_err(commit_error)?;
+        credit_guild(&mut guilds, guild.as_deref(), &pubkey, delta, &meta);
+        let unlocked =
+            check_event_achievements(state.as_ref(), &mut sessions, &achievements, &pubkey, (&payload).into());
+        if let Some(session) = sessions.get(&pubkey) {
+            log_session_root(&pubkey, session);
+        }
+        (delta, bonuses, link, payload.tier_change, unlocked)
     };
-    {
-        let mut vec = state.write().unwrap();
-        vec.push(payload.clone());
+
+    let tier = sessions.read().get(&pubkey).map(|s| s.tier(&tiers).name.clone());
+    Ok(Json(serde_json::json!({
+        "ok": true, "points_awarded": delta, "base_points": delta - bonuses.iter().map(Bonus::points).sum::<u32>(),
+        "bonuses": bonuses, "seq": link.seq, "tier": tier, "tier_change": tier_change,
+        "achievements_unlocked": unlocked
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::StatusCode;
+
+    use crate::handlers::submission::witness_cap;
+    use crate::testutil::{app_state, mine, submit, temp_dir, wallet, witness};
+
+    fn node(b: u8) -> String {
+        format!("0x{}", hex::encode([b; 64]))
+    }
+
+    fn proof(siblings: Vec<String>, path: &str, root: Option<String>) -> ProofJson {
+        ProofJson { siblings, path: path.into(), root }
+    }
+
+    #[test]
+    fn path_bits_are_leaf_level_first() {
+        assert_eq!(path_index(""), Some(0));
+        assert_eq!(path_index("1"), Some(1));
+        assert_eq!(path_index("01"), Some(2));
+        assert_eq!(path_index("110"), Some(3));
+        assert_eq!(path_index("12"), None);
+    }
+
+    #[test]
+    fn decodes_nodes_path_and_root() {
+        let decoded = decode_proof(&proof(vec![node(1), node(2)], "101", Some(node(9)))).unwrap();
+        assert_eq!(decoded.siblings, vec![[1u8; 64], [2u8; 64]]);
+        assert_eq!(decoded.index, 5);
+        assert_eq!(decoded.root, Some([9u8; 64]));
+    }
+
+    #[test]
+    fn rejects_malformed_proofs() {
+        let field = |p: ProofJson| decode_proof(&p).err().map(|(_, msg)| msg);
+        let unprefixed = hex::encode([1u8; 64]);
+        assert_eq!(field(proof(vec![unprefixed], "0", None)).as_deref(), Some("invalid proof.siblings"));
+        assert_eq!(field(proof(vec!["0x00".into()], "0", None)).as_deref(), Some("invalid proof.siblings"));
+        // fewer path bits than siblings
+        assert_eq!(field(proof(vec![node(1), node(2)], "1", None)).as_deref(), Some("invalid proof.path"));
+        assert_eq!(field(proof(vec![node(1)], "1x", None)).as_deref(), Some("invalid proof.path"));
+        assert_eq!(field(proof(vec![], &"0".repeat(65), None)).as_deref(), Some("invalid proof.path"));
+        assert_eq!(field(proof(vec![node(1)], "1", Some("0x12".into()))).as_deref(), Some("invalid proof.root"));
     }
-    if let Ok(mut f) = OpenOptions::new().create(true).append(true).open("logs/submissions.jsonl") {
-        let _ = writeln!(f, "{}", serde_json::to_string(&payload).unwrap());
+
+    #[cfg(feature = "proofs")]
+    #[test]
+    fn published_proofs_decode_and_verify() {
+        use crate::state::pki::PubKey;
+        use crate::store::witness_roots::WitnessRoots;
+        use crate::testutil::temp_dir;
+
+        let dir = temp_dir("witness-decode");
+        let roots = WitnessRoots::open(dir.join("w.jsonl").to_str().unwrap()).unwrap();
+        let wallets: Vec<PubKey> = (1..=5u8).map(|b| PubKey::new([b; 32])).collect();
+        roots.publish(7, &wallets).unwrap();
+        let (root, count) = roots.root_for(7).unwrap();
+
+        for (i, wallet) in wallets.iter().enumerate() {
+            let published = roots.proof_for(7, wallet).unwrap();
+            let json = proof(published.siblings, &published.path, Some(published.root));
+            let decoded = decode_proof(&json).unwrap();
+            assert_eq!((decoded.index, decoded.root), (i, Some(root)));
+            assert_eq!(json.path.len(), merkle_depth(count));
+            let leaf = compute_leaf_from_wallet(wallet);
+            assert!(verify_merkle_proof(leaf, decoded.siblings.clone(), root, decoded.index, count));
+            // another wallet's leaf does not verify with this proof
+            let other = compute_leaf_from_wallet(&wallets[(i + 1) % wallets.len()]);
+            assert!(!verify_merkle_proof(other, decoded.siblings, root, decoded.index, count));
+        }
+    }
+
+    fn logged(app: &AppState) -> usize {
+        app.store.all().unwrap().len()
+    }
+
+    #[tokio::test]
+    async fn a_wallet_cannot_witness_its_own_block() {
+        let dir = temp_dir("witness-self");
+        let app = app_state(&dir);
+        let a = wallet('a');
+        mine(&app, &a, None, 18, 5).await.unwrap();
+
+        let before = logged(&app);
+        let err = witness(&app, &a, 18, 5).await.unwrap_err();
+        assert_eq!(err.status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(err.body["error"], "self_witness");
+        assert_eq!(logged(&app), before);
     }
 
-    Ok(Json(serde_json::json!({ "ok": true, "points_awarded": delta })))
+    #[tokio::test]
+    async fn witness_needs_a_mined_block() {
+        let dir = temp_dir("witness-unmined");
+        let app = app_state(&dir);
+
+        let err = witness(&app, &wallet('a'), 18, 5).await.unwrap_err();
+        assert_eq!(err.status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(err.body["error"], "witness_without_mined_block");
+        assert_eq!(logged(&app), 0);
+    }
+
+    #[tokio::test]
+    async fn witnesses_past_the_cap_are_refused() {
+        let dir = temp_dir("witness-cap");
+        let app = app_state(&dir);
+        mine(&app, &wallet('0'), None, 18, 5).await.unwrap();
+        let witnesses: Vec<String> = "123456789ab".chars().map(wallet).collect();
+        #[cfg(feature = "proofs")]
+        {
+            let keys: Vec<_> = witnesses.iter().map(|w| decode_pubkey_from_hex(w).unwrap()).collect();
+            app.witness_roots.publish(5, &keys).unwrap();
+        }
+
+        let (within, over) = witnesses.split_at(witness_cap());
+        for w in within {
+            witness(&app, w, 18, 5).await.unwrap();
+        }
+        let before = logged(&app);
+        let err = witness(&app, &over[0], 18, 5).await.unwrap_err();
+        assert_eq!(err.status, StatusCode::CONFLICT);
+        assert_eq!(err.body["error"], "witness_cap_reached");
+        assert_eq!(err.body["cap"], witness_cap());
+        assert_eq!(logged(&app), before);
+    }
+
+    #[tokio::test]
+    async fn legacy_submit_checks_witnesses_too() {
+        let dir = temp_dir("witness-legacy");
+        let app = app_state(&dir);
+        let (a, b) = (wallet('a'), wallet('b'));
+        let record = |w: &str, height: u64| {
+            serde_json::json!({
+                "wallet": w, "score": 0, "mu_level": 18, "block_height": height, "event_type": "witness"
+            })
+        };
+
+        let err = submit(&app, record(&a, 5)).await.unwrap_err();
+        assert_eq!(err.body["error"], "witness_without_mined_block");
+        assert_eq!(logged(&app), 0);
+
+        mine(&app, &a, None, 18, 5).await.unwrap();
+        let before = logged(&app);
+        let err = submit(&app, record(&a, 5)).await.unwrap_err();
+        assert_eq!(err.status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(err.body["error"], "self_witness");
+        assert_eq!(logged(&app), before);
+
+        submit(&app, record(&b, 5)).await.unwrap();
+        assert_eq!(app.store.by_wallet(&b).unwrap()[0].event_type, "witness");
+    }
 }
@@ -0,0 +1,81 @@
+// backend/src/handlers/witness_roots.rs
+// Indexer/admin hook: publish the witness set of a superblock, so witness
+// proofs are checked against a root the server trusts.
+
+use std::sync::Arc;
+
+use axum::{extract::{Path, State}, http::{HeaderMap, StatusCode}, Json};
+use serde::Deserialize;
+
+use crate::handlers::health::HealthState;
+use crate::handlers::submission::{decode_pubkey_from_hex, store_error, AppState};
+use crate::store::witness_roots::{Publish, WitnessProof, WitnessRoot, WitnessRoots};
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WitnessRootReq {
+    pub block_height: u64,
+    /// Hex wallets of the witness set; their order is the leaf order.
+    pub wallets: Vec<String>,
+}
+
+pub async fn post_witness_root(
+    State((app, h)): State<(AppState, HealthState)>,
+    headers: HeaderMap,
+    Json(req): Json<WitnessRootReq>,
+) -> Result<Json<WitnessRoot>, (StatusCode, String)> {
+    let need = h.admin_token.clone().unwrap_or_default();
+    let got = headers.get("x-admin-token").and_then(|v| v.to_str().ok()).unwrap_or("");
+    if need.is_empty() || need != got {
+        return Err((StatusCode::UNAUTHORIZED, "unauthorized".into()));
+    }
+    if req.block_height == 0 {
+        return Err((StatusCode::BAD_REQUEST, "block_height must be > 0".into()));
+    }
+    if req.wallets.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "wallets must not be empty".into()));
+    }
+    let wallets = req.wallets.iter().map(|w| decode_pubkey_from_hex(w)).collect::<Result<Vec<_>, _>>()?;
+    if wallets.iter().enumerate().any(|(i, w)| wallets[..i].contains(w)) {
+        return Err((StatusCode::BAD_REQUEST, "wallets must be distinct".into()));
+    }
+
+    match app.witness_roots.publish(req.block_height, &wallets).map_err(store_error)? {
+        Publish::Added(root) => {
+            println!(
+                "🌿 Witness root for block {}: {}… ({} wallets)",
+                root.block_height,
+                &root.root[..16],
+                root.wallets.len()
+            );
+            Ok(Json(root))
+        }
+        Publish::Unchanged(root) => Ok(Json(root)),
+        Publish::Conflict(old) => Err((
+            StatusCode::CONFLICT,
+            format!("block {} already has witness root {}", old.block_height, old.root),
+        )),
+    }
+}
+
+pub async fn get_witness_root(
+    State(roots): State<Arc<WitnessRoots>>,
+    Path(block_height): Path<u64>,
+) -> Result<Json<WitnessRoot>, (StatusCode, String)> {
+    roots
+        .get(block_height)
+        .map(Json)
+        .ok_or((StatusCode::NOT_FOUND, format!("no witness root for block {block_height}")))
+}
+
+/// The proof a witness sends to `/submit/witness` for this block.
+pub async fn get_witness_proof(
+    State(roots): State<Arc<WitnessRoots>>,
+    Path((block_height, wallet)): Path<(u64, String)>,
+) -> Result<Json<WitnessProof>, (StatusCode, String)> {
+    let pubkey = decode_pubkey_from_hex(&wallet)?;
+    roots
+        .proof_for(block_height, &pubkey)
+        .map(Json)
+        .ok_or((StatusCode::NOT_FOUND, format!("wallet is not in the witness set of block {block_height}")))
+}
@@ -0,0 +1,12 @@
+// backend/src/lib.rs
+// Shared by the server (main.rs) and the offline tools in src/bin.
+
+pub mod handlers;
+pub mod engine;
+pub mod store;
+#[path = "state/lib.rs"]
+pub mod state;
+#[path = "episode.rs"]
+pub mod episode;
+#[cfg(test)]
+mod testutil;
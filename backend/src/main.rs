@@ -1,10 +1,3 @@
-mod handlers;
-mod engine;
-#[path = "state/lib.rs"]
-mod state;
-#[path = "episode.rs"]
-mod episode;
-
 use axum::{
     extract::{DefaultBodyLimit, State},
     http::{header, HeaderMap, HeaderValue, Method},
@@ -17,12 +10,21 @@ use tower_http::{
     timeout::TimeoutLayer,
 };
 
+use backend::{engine::{achievement::AchievementBook, rules::RulesHandle, tier::TierTable}, handlers, state, store};
+use handlers::achievements::get_wallet_achievements;
+use handlers::audit::get_audit_chain;
 use handlers::events::get_events;
+use handlers::guilds::{get_guild_leaderboard, post_join, post_leave};
 use handlers::health::{get_health, override_health, HealthState, Metrics, StatusKind};
 use handlers::leaderboard::get_leaderboard;
-use handlers::submission::{handle_submission, load_submissions_from_jsonl, SharedState, Submission};
+use handlers::reorg::post_reorg;
+use handlers::rules::{get_rules, reload_rules};
+use handlers::submission::{handle_submission, replay_into_sessions, AppState, SharedState};
 use handlers::submit_mine::submit_mine;
 use handlers::submit_witness::submit_witness;
+use handlers::seasons::get_seasons;
+use handlers::state_root::get_state_root;
+use state::{root::log_state_root, seasons::{self, Seasons}, snapshot, Guilds, Sessions};
 
 #[tokio::main]
 async fn main() {
@@ -46,6 +48,7 @@ async fn main() {
                 header::CONTENT_TYPE,
                 header::HeaderName::from_static("x-mue-key"),
                 header::HeaderName::from_static("x-admin-token"),
+                header::HeaderName::from_static("idempotency-key"),
             ])
     } else {
         CorsLayer::new()
@@ -55,12 +58,105 @@ async fn main() {
                 header::CONTENT_TYPE,
                 header::HeaderName::from_static("x-mue-key"),
                 header::HeaderName::from_static("x-admin-token"),
+                header::HeaderName::from_static("idempotency-key"),
             ])
     };
 
     // --- shared states
-    let initial: Vec<Submission> = load_submissions_from_jsonl("logs/submissions.jsonl");
-    let submissions_state: SharedState = Arc::new(RwLock::new(initial));
+    let submissions_state: SharedState =
+        store::open_from_env().expect("failed to open submission store");
+    #[cfg(feature = "proofs")]
+    let batching = Arc::new(
+        store::batch::BatchingStore::open_from_env(submissions_state.clone())
+            .expect("failed to open batch log"),
+    );
+    #[cfg(feature = "proofs")]
+    let submissions_state: SharedState = batching.clone();
+    #[cfg(feature = "proofs")]
+    let witness_roots = Arc::new(
+        store::witness_roots::WitnessRoots::open_from_env().expect("failed to open witness roots"),
+    );
+    #[cfg(feature = "proofs")]
+    if !witness_roots.is_empty() {
+        println!("🌿 {} witness roots loaded", witness_roots.len());
+    }
+    match store::chain::verify_chain(submissions_state.as_ref()) {
+        Ok(r) => match r.first_broken {
+            None => println!("🔗 Audit chain intact ({} records, head {}…)", r.records, &r.head[..16]),
+            Some(b) => eprintln!("⚠️  Audit chain broken at record {}: {}", b.position, b.reason),
+        },
+        Err(e) => eprintln!("⚠️  Could not verify audit chain: {e}"),
+    }
+
+    let rules = Arc::new(
+        RulesHandle::from_env().unwrap_or_else(|e| panic!("invalid scoring rules: {e}")),
+    );
+    match rules.path() {
+        Some(p) => println!("📐 Scoring rules {} from {}", rules.current().id(), p.display()),
+        None => println!("📐 Scoring rules {} (built-in)", rules.current().id()),
+    }
+    let rules_history = Arc::new(
+        store::rules_history::RulesHistory::open(&store::rules_history::history_path())
+            .expect("failed to open rules history"),
+    );
+    rules_history.record(&rules.current()).expect("failed to record scoring rules");
+    println!("📐 {} scoring tables in the rules history", rules_history.len());
+
+    let seasons = Arc::new(Seasons::from_env().unwrap_or_else(|e| panic!("invalid seasons: {e}")));
+    if !seasons.calendar().seasons().is_empty() {
+        println!(
+            "🏆 {} seasons configured, archives in {}",
+            seasons.calendar().seasons().len(),
+            seasons.dir().display()
+        );
+    }
+
+    let achievements = Arc::new(
+        AchievementBook::from_env().unwrap_or_else(|e| panic!("invalid achievements: {e}")),
+    );
+
+    // newest valid snapshot first, then only the log tail after it
+    let sessions = Arc::new(Sessions::new());
+    let guilds = Arc::new(Guilds::new());
+    let snap_dir = snapshot::snapshot_dir();
+    let log_end = submissions_state.cursor().expect("failed to read submission store");
+    let resume_at = match snapshot::load_latest(&snap_dir, log_end) {
+        Ok(Some(snap)) => {
+            let cursor = snap.cursor;
+            println!(
+                "📸 Loaded snapshot at cursor {cursor} ({} sessions, {} guilds)",
+                snap.sessions.len(),
+                snap.guilds.len()
+            );
+            snapshot::restore(&sessions, &guilds, snap);
+            cursor
+        }
+        Ok(None) => 0,
+        Err(e) => {
+            eprintln!("⚠️  Could not read snapshots: {e}");
+            0
+        }
+    };
+    let replay = replay_into_sessions(
+        submissions_state.as_ref(),
+        &sessions,
+        &guilds,
+        &rules.current(),
+        &rules_history,
+        seasons.calendar(),
+        resume_at,
+    )
+        .expect("failed to replay submission store");
+    println!(
+        "🔁 Replayed {} records into sessions ({} skipped, {} rejected, {} reverted)",
+        replay.replayed, replay.skipped, replay.rejected, replay.reverted
+    );
+    log_state_root(sessions.read().iter());
+    if let Some(every) = snapshot::snapshot_interval() {
+        snapshot::spawn_timer(snap_dir, submissions_state.clone(), sessions.clone(), guilds.clone(), every);
+    }
+    #[cfg(feature = "proofs")]
+    store::batch::spawn_timer(batching.clone(), store::batch::batch_interval());
 
     let health_state = HealthState {
         metrics: Arc::new(RwLock::new(Metrics {
@@ -77,6 +173,31 @@ async fn main() {
         admin_token: std::env::var("ADMIN_TOKEN").ok(),
     };
 
+    let tiers = TierTable::from_env().unwrap_or_else(|e| {
+        eprintln!("⚠️ TIER_THRESHOLDS ignored ({e}); using the default tiers");
+        TierTable::default()
+    });
+    let app_state = AppState {
+        store: submissions_state.clone(),
+        sessions: sessions.clone(),
+        tiers: Arc::new(tiers),
+        rules: rules.clone(),
+        rules_history: rules_history.clone(),
+        seasons: seasons.clone(),
+        achievements: achievements.clone(),
+        guilds: guilds.clone(),
+        #[cfg(feature = "proofs")]
+        witness_roots: witness_roots.clone(),
+    };
+    seasons::spawn_timer(
+        seasons.clone(),
+        submissions_state.clone(),
+        sessions.clone(),
+        app_state.tiers.clone(),
+        achievements.clone(),
+        seasons::check_interval(),
+    );
+
     // --- routers by state type
     let api_router = Router::new()
         .route("/submit", post(handle_submission)) // legacy/compat
@@ -84,23 +205,52 @@ async fn main() {
         .route("/submit/witness", post(submit_witness))
         .route("/events", get(get_events))
         .route("/leaderboard", get(get_leaderboard))
-        .with_state(submissions_state.clone());
+        .route("/rules", get(get_rules))
+        .route("/seasons", get(get_seasons))
+        .route("/wallets/:id/achievements", get(get_wallet_achievements))
+        .route("/guilds/:name/join", post(post_join))
+        .route("/guilds/:name/leave", post(post_leave))
+        .route("/leaderboard/guilds", get(get_guild_leaderboard))
+        .route("/state/root", get(get_state_root))
+        .with_state(app_state.clone());
 
     let health_router = Router::new()
         .route("/health", get(get_health))
         .route("/health/:which", post(override_health))
         .with_state(health_state.clone());
 
-    // /reset needs *both* states; use a tiny router with tuple state
-    let reset_router = Router::new()
+    // /reset and /admin/* need *both* states; use a tiny router with tuple state
+    let admin_router = Router::new()
         .route("/reset", post(reset_dev_only))
-        .with_state((submissions_state.clone(), health_state.clone()));
+        .route("/admin/reorg", post(post_reorg))
+        .route("/admin/rules/reload", post(reload_rules))
+        .route("/audit/chain", get(get_audit_chain));
+    #[cfg(feature = "proofs")]
+    let admin_router =
+        admin_router.route("/admin/witness-roots", post(handlers::witness_roots::post_witness_root));
+    let admin_router = admin_router.with_state((app_state.clone(), health_state.clone()));
 
     // --- compose
     let app = Router::new()
         .merge(api_router)
         .merge(health_router)
-        .merge(reset_router)
+        .merge(admin_router);
+
+    #[cfg(feature = "proofs")]
+    let app = app
+        .merge(
+            Router::new()
+                .route("/proofs/:seq", get(handlers::proofs::get_proof))
+                .with_state(batching.clone()),
+        )
+        .merge(
+            Router::new()
+                .route("/witness-roots/:height", get(handlers::witness_roots::get_witness_root))
+                .route("/witness-roots/:height/:wallet", get(handlers::witness_roots::get_witness_proof))
+                .with_state(witness_roots.clone()),
+        );
+
+    let app = app
         .layer(DefaultBodyLimit::max(32 * 1024)) // 32KB
         .layer(TimeoutLayer::new(Duration::from_secs(10)))
         .layer(cors);
@@ -115,7 +265,7 @@ async fn main() {
 
 // --- dev-only reset (requires x-admin-token) ---
 async fn reset_dev_only(
-    State((submissions, h)): State<(SharedState, HealthState)>,
+    State((app, h)): State<(AppState, HealthState)>,
     headers: HeaderMap,
 ) -> Result<Json<serde_json::Value>, (axum::http::StatusCode, String)> {
     if h.is_prod {
@@ -126,6 +276,23 @@ async fn reset_dev_only(
     if need.is_empty() || need != got {
         return Err((axum::http::StatusCode::UNAUTHORIZED, "unauthorized".into()));
     }
-    submissions.write().unwrap().clear();
+    {
+        let mut sessions = app.sessions.write();
+        app.store.clear().map_err(|e| {
+            (axum::http::StatusCode::INTERNAL_SERVER_ERROR, format!("store error: {e}"))
+        })?;
+        app.guilds.write().clear();
+        sessions.clear();
+    }
+    #[cfg(feature = "proofs")]
+    app.witness_roots.clear().map_err(|e| {
+        (axum::http::StatusCode::INTERNAL_SERVER_ERROR, format!("witness roots error: {e}"))
+    })?;
+    app.seasons.purge().map_err(|e| {
+        (axum::http::StatusCode::INTERNAL_SERVER_ERROR, format!("season archive error: {e}"))
+    })?;
+    snapshot::purge(&snapshot::snapshot_dir()).map_err(|e| {
+        (axum::http::StatusCode::INTERNAL_SERVER_ERROR, format!("snapshot error: {e}"))
+    })?;
     Ok(Json(serde_json::json!({ "ok": true })))
 }
@@ -1,13 +1,16 @@
-use std::collections::HashMap;
-use std::sync::RwLock;
-use once_cell::sync::Lazy;
-
-use crate::state::pki::PubKey;
+use crate::engine::guild::GuildSession;
 use crate::engine::kdapp::MueHeroSession;
 
+pub mod manager;
 pub mod pki;
+pub mod root;
+pub mod seasons;
+pub mod snapshot;
 pub mod types;
 
-/// Global session manager: tracks per-wallet game sessions
-pub static SESSIONS: Lazy<RwLock<HashMap<PubKey, MueHeroSession>>> =
-    Lazy::new(|| RwLock::new(HashMap::new()));
+/// Per-wallet game sessions, injected into handlers through axum state.
+pub type Sessions = manager::EpisodeManager<MueHeroSession>;
+
+/// Guild episodes, keyed by guild name. When both are needed, lock
+/// `Sessions` first.
+pub type Guilds = manager::EpisodeManager<GuildSession, String>;
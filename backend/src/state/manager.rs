@@ -0,0 +1,188 @@
+// backend/src/state/manager.rs
+
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    sync::{RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+use crate::episode::{Episode, EpisodeError, PayloadMetadata};
+use crate::state::pki::PubKey;
+
+/// Owns every live episode of one type, keyed by `K` (a wallet by default),
+/// and routes commands to them. Shared through axum state.
+///
+/// Take `write()` once per request and keep it across execute + persist, so
+/// that anything reading under `read()` (snapshots) sees both or neither.
+pub struct EpisodeManager<E: Episode, K = PubKey> {
+    episodes: RwLock<HashMap<K, E>>,
+}
+
+/// Why `Episodes::commit` did not go through.
+#[derive(Debug)]
+pub enum CommitError<C, P> {
+    /// The episode refused the command; nothing changed.
+    Command(EpisodeError<C>),
+    /// Persisting failed; the command was rolled back.
+    Persist(P),
+}
+
+impl<E: Episode, K: Eq + Hash + Clone> Default for EpisodeManager<E, K> {
+    fn default() -> Self {
+        Self { episodes: RwLock::new(HashMap::new()) }
+    }
+}
+
+impl<E: Episode, K: Eq + Hash + Clone> EpisodeManager<E, K> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn read(&self) -> RwLockReadGuard<'_, HashMap<K, E>> {
+        self.episodes.read().unwrap()
+    }
+
+    pub fn write(&self) -> Episodes<'_, E, K> {
+        Episodes { map: self.episodes.write().unwrap() }
+    }
+
+    /// Swaps in a whole set of episodes (snapshot restore).
+    pub fn replace(&self, episodes: HashMap<K, E>) {
+        *self.episodes.write().unwrap() = episodes;
+    }
+}
+
+/// Exclusive access to the episodes, held for the length of one request.
+pub struct Episodes<'a, E: Episode, K> {
+    map: RwLockWriteGuard<'a, HashMap<K, E>>,
+}
+
+impl<E: Episode, K: Eq + Hash + Clone> Episodes<'_, E, K> {
+    pub fn get(&self, id: &K) -> Option<&E> {
+        self.map.get(id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &E)> {
+        self.map.iter()
+    }
+
+    /// Direct access for state kept beside the commands (e.g. unlocks
+    /// restored from the log); scoring goes through `execute`.
+    pub fn get_mut(&mut self, id: &K) -> Option<&mut E> {
+        self.map.get_mut(id)
+    }
+
+    /// Runs `cmd` in `id`'s episode, initializing it with `participants` on
+    /// first use. An episode created for a refused command is dropped again.
+    pub fn execute(
+        &mut self,
+        id: &K,
+        participants: &[PubKey],
+        cmd: &E::Command,
+        auth: Option<PubKey>,
+        meta: &PayloadMetadata,
+    ) -> Result<E::CommandRollback, EpisodeError<E::CommandError>> {
+        let created = !self.map.contains_key(id);
+        let episode = self
+            .map
+            .entry(id.clone())
+            .or_insert_with(|| E::initialize(participants.to_vec(), meta));
+        let res = episode.execute(cmd, auth, meta);
+        if res.is_err() && created {
+            self.map.remove(id);
+        }
+        res
+    }
+
+    pub fn rollback(&mut self, id: &K, rollback: E::CommandRollback) -> bool {
+        self.map.get_mut(id).is_some_and(|e| e.rollback(rollback))
+    }
+
+    /// `execute`, then `persist` the result while still holding the lock. If
+    /// persisting fails the command is rolled back and the error returned.
+    pub fn commit<T, P>(
+        &mut self,
+        id: &K,
+        participants: &[PubKey],
+        cmd: &E::Command,
+        auth: Option<PubKey>,
+        meta: &PayloadMetadata,
+        persist: impl FnOnce(&E::CommandRollback) -> Result<T, P>,
+    ) -> Result<T, CommitError<E::CommandError, P>> {
+        let created = !self.map.contains_key(id);
+        let rollback = self
+            .execute(id, participants, cmd, auth, meta)
+            .map_err(CommitError::Command)?;
+        match persist(&rollback) {
+            Ok(out) => Ok(out),
+            Err(e) => {
+                if created {
+                    self.map.remove(id);
+                } else {
+                    self.rollback(id, rollback);
+                }
+                Err(CommitError::Persist(e))
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.map.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::guild::{GuildCommand, GuildError, GuildSession};
+
+    type Guilds = EpisodeManager<GuildSession, String>;
+
+    const META: PayloadMetadata = PayloadMetadata { accepting_time: 1_735_689_600 };
+
+    fn commit(
+        guilds: &Guilds,
+        member: &PubKey,
+        cmd: GuildCommand,
+        persist_ok: bool,
+    ) -> Result<(), CommitError<GuildError, &'static str>> {
+        let name = "miners".to_string();
+        guilds.write().commit(&name, std::slice::from_ref(member), &cmd, Some(member.clone()), &META, |_| {
+            if persist_ok { Ok(()) } else { Err("disk full") }
+        })
+    }
+
+    #[test]
+    fn a_failed_persist_drops_an_episode_it_created() {
+        let guilds = Guilds::new();
+        let a = PubKey::new([1; 32]);
+        let err = commit(&guilds, &a, GuildCommand::Found { member: a.clone() }, false).unwrap_err();
+        assert!(matches!(err, CommitError::Persist("disk full")));
+        assert!(guilds.read().is_empty());
+    }
+
+    #[test]
+    fn a_failed_persist_rolls_an_existing_episode_back() {
+        let guilds = Guilds::new();
+        let (a, b) = (PubKey::new([1; 32]), PubKey::new([2; 32]));
+        commit(&guilds, &a, GuildCommand::Found { member: a.clone() }, true).unwrap();
+        commit(&guilds, &a, GuildCommand::Credit { member: a.clone(), points: 15 }, true).unwrap();
+
+        let err = commit(&guilds, &a, GuildCommand::Credit { member: a.clone(), points: 20 }, false).unwrap_err();
+        assert!(matches!(err, CommitError::Persist(_)));
+        let err = commit(&guilds, &b, GuildCommand::Join { member: b.clone() }, false).unwrap_err();
+        assert!(matches!(err, CommitError::Persist(_)));
+        let guild = guilds.read()["miners"].clone();
+        assert_eq!((guild.score(), guild.members()), (15, std::slice::from_ref(&a)));
+    }
+
+    #[test]
+    fn a_refused_command_never_reaches_persist() {
+        let guilds = Guilds::new();
+        let a = PubKey::new([1; 32]);
+        // a fresh episode already holds its participant, so joining it is refused
+        let err = commit(&guilds, &a, GuildCommand::Join { member: a.clone() }, true).unwrap_err();
+        assert!(matches!(err, CommitError::Command(EpisodeError::CommandError(GuildError::AlreadyMember))));
+        assert!(guilds.read().is_empty(), "an episode created for a refused command is dropped");
+    }
+}
@@ -5,7 +5,7 @@ use serde::{Serialize, Deserialize};
 use borsh::{BorshDeserialize, BorshSerialize};
 use hex;
 
-#[derive(Clone, PartialEq, Eq, Hash, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, BorshSerialize, BorshDeserialize, Serialize, Deserialize)]
 pub struct PubKey([u8; 32]);
 
 
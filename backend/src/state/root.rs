@@ -0,0 +1,149 @@
+// backend/src/state/root.rs
+
+use blake2::{Blake2b512, Digest};
+use borsh::BorshSerialize;
+
+use crate::engine::kdapp::MueHeroSession;
+use crate::state::pki::PubKey;
+
+/// A Blake2b-512 state hash.
+pub type Root = [u8; 64];
+
+/// Hash of the session's Borsh encoding. Sessions keep their state in
+/// ordered maps only, so equal state always encodes (and hashes) the same.
+pub fn session_root(session: &MueHeroSession) -> Root {
+    let bytes = session.try_to_vec().expect("session serializes");
+    let mut out = [0u8; 64];
+    out.copy_from_slice(&Blake2b512::digest(bytes));
+    out
+}
+
+/// Every session's root, in wallet byte order.
+pub fn session_roots<'a>(
+    sessions: impl IntoIterator<Item = (&'a PubKey, &'a MueHeroSession)>,
+) -> Vec<(PubKey, Root)> {
+    let mut roots: Vec<(PubKey, Root)> =
+        sessions.into_iter().map(|(pk, s)| (pk.clone(), session_root(s))).collect();
+    roots.sort_by(|a, b| a.0.as_bytes().cmp(b.0.as_bytes()));
+    roots
+}
+
+/// Root of the whole session map: the session count (u64 LE), then each
+/// wallet and its session root, in the order `session_roots` returns them.
+pub fn state_root(roots: &[(PubKey, Root)]) -> Root {
+    let mut hasher = Blake2b512::new();
+    hasher.update((roots.len() as u64).to_le_bytes());
+    for (pk, root) in roots {
+        hasher.update(pk.as_bytes());
+        hasher.update(root);
+    }
+    let mut out = [0u8; 64];
+    out.copy_from_slice(&hasher.finalize());
+    out
+}
+
+/// Logs the state root. Hashes every session, so it is kept to startup and
+/// the rare commands that touch many wallets (reorgs, season rollover);
+/// `GET /state/root` computes it on demand.
+pub fn log_state_root<'a>(sessions: impl IntoIterator<Item = (&'a PubKey, &'a MueHeroSession)>) {
+    let roots = session_roots(sessions);
+    let root = hex::encode(state_root(&roots));
+    println!("🌳 State root {}… ({} sessions)", &root[..16], roots.len());
+}
+
+/// Logs the root of the one session a command changed, without touching
+/// the others.
+pub fn log_session_root(wallet: &PubKey, session: &MueHeroSession) {
+    println!("🌳 Session root {} → {}…", hex::encode(wallet.as_bytes()), &hex::encode(session_root(session))[..16]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::season::SeasonCalendar;
+    use crate::handlers::reorg::orphan_blocks;
+    use crate::handlers::submission::{replay_into_sessions, AppState};
+    use crate::state::{Guilds, Sessions};
+    use crate::store::rules_history::RulesHistory;
+    use crate::testutil::{admin_headers, app_state, health_state, mine, temp_dir, wallet, witness};
+
+    fn root_of(sessions: &Sessions) -> Root {
+        state_root(&session_roots(sessions.read().iter()))
+    }
+
+    fn replay(app: &AppState) -> Root {
+        replay_with(app, &app.rules_history)
+    }
+
+    fn replay_with(app: &AppState, history: &RulesHistory) -> Root {
+        let (sessions, guilds) = (Sessions::new(), Guilds::new());
+        let rules = app.rules.current();
+        let calendar = SeasonCalendar::default();
+        replay_into_sessions(app.store.as_ref(), &sessions, &guilds, &rules, history, &calendar, 0).unwrap();
+        root_of(&sessions)
+    }
+
+    #[tokio::test]
+    async fn replays_of_the_same_log_agree_on_the_root() {
+        let dir = temp_dir("state-root");
+        let app = app_state(&dir);
+        let (a, b, c) = (wallet('a'), wallet('b'), wallet('c'));
+        mine(&app, &c, None, 18, 3).await.unwrap();
+        mine(&app, &a, None, 16, 4).await.unwrap();
+        mine(&app, &b, None, 17, 5).await.unwrap();
+        mine(&app, &a, None, 18, 6).await.unwrap();
+        orphan_blocks(app.store.as_ref(), &app.sessions, &app.guilds, &app.tiers, 6).unwrap();
+
+        let live = root_of(&app.sessions);
+        assert_eq!(replay(&app), live);
+        assert_eq!(replay(&app), live);
+
+        mine(&app, &b, None, 16, 7).await.unwrap();
+        assert_ne!(replay(&app), live);
+        assert_eq!(replay(&app), root_of(&app.sessions));
+    }
+
+    #[test]
+    fn map_root_does_not_depend_on_iteration_order() {
+        let sessions: Vec<(PubKey, MueHeroSession)> = ['c', 'a', 'b']
+            .into_iter()
+            .map(|c| (PubKey::new([c as u8; 32]), MueHeroSession::default()))
+            .collect();
+        let forward = session_roots(sessions.iter().map(|(pk, s)| (pk, s)));
+        let backward = session_roots(sessions.iter().rev().map(|(pk, s)| (pk, s)));
+        assert_eq!(forward, backward);
+        assert_eq!(state_root(&forward), state_root(&backward));
+        assert_ne!(state_root(&forward), state_root(&forward[1..]));
+    }
+
+    #[tokio::test]
+    async fn replay_follows_the_bonus_rules_each_record_was_scored_with() {
+        use crate::engine::rules::RulesHandle;
+        use crate::handlers::rules::reload_rules;
+        use axum::{extract::State, Json};
+        use std::sync::Arc;
+
+        let dir = temp_dir("state-root-rules");
+        let path = dir.join("rules.toml");
+        let table = "min_mu_level = 15\n[mined]\n15 = 15\n[witness]\n15 = 7\n";
+        std::fs::write(&path, table).unwrap();
+        let app = AppState { rules: Arc::new(RulesHandle::open(Some(path.clone())).unwrap()), ..app_state(&dir) };
+        app.rules_history.record(&app.rules.current()).unwrap();
+
+        // without a combo rule the witness leaves a's mined block open...
+        let (a, b) = (wallet('a'), wallet('b'));
+        mine(&app, &b, None, 18, 5).await.unwrap();
+        mine(&app, &a, None, 18, 6).await.unwrap();
+        witness(&app, &a, 18, 5).await.unwrap();
+        // ...which replaying it under the reloaded combo rule would close
+        std::fs::write(&path, format!("{table}[combo]\nwithin_minutes = 30\npoints = 25\n")).unwrap();
+        let Json(reloaded) = reload_rules(State((app.clone(), health_state())), admin_headers()).await.unwrap();
+        assert_eq!(reloaded["id"], app.rules.current().id());
+        mine(&app, &b, None, 18, 7).await.unwrap();
+
+        assert_eq!(app.rules_history.len(), 2);
+        assert_eq!(replay(&app), root_of(&app.sessions));
+        let forgotten = RulesHistory::open_read_only(&dir.join("missing.jsonl")).unwrap();
+        assert_ne!(replay_with(&app, &forgotten), root_of(&app.sessions));
+    }
+}
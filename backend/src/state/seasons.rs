@@ -0,0 +1,324 @@
+// backend/src/state/seasons.rs
+
+use std::{
+    collections::HashSet,
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::engine::achievement::AchievementBook;
+use crate::engine::kdapp::SeasonRef;
+use crate::engine::season::{Season, SeasonCalendar};
+use crate::engine::tier::TierTable;
+use crate::handlers::achievements::{unlock_and_log, Trigger};
+use crate::handlers::leaderboard::{build_leaderboard, LeaderboardEntry, Ranking};
+use crate::state::{root::log_state_root, Sessions};
+use crate::store::SubmissionStore;
+
+/// Final standings of an ended season, written once at rollover.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SeasonArchive {
+    pub season: Season,
+    pub archived_at: DateTime<Utc>,
+    pub standings: Vec<LeaderboardEntry>,
+}
+
+/// The season calendar plus which seasons are already frozen.
+pub struct Seasons {
+    calendar: SeasonCalendar,
+    dir: PathBuf,
+    archived: RwLock<HashSet<String>>,
+}
+
+/// `SEASON_ARCHIVE_DIR`, default `logs/seasons`.
+pub fn archive_dir() -> PathBuf {
+    std::env::var("SEASON_ARCHIVE_DIR").unwrap_or_else(|_| "logs/seasons".into()).into()
+}
+
+/// `SEASON_CHECK_SECS`, default 60: how often to look for ended seasons.
+pub fn check_interval() -> Duration {
+    let secs = std::env::var("SEASON_CHECK_SECS")
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .filter(|&s| s > 0)
+        .unwrap_or(60);
+    Duration::from_secs(secs)
+}
+
+fn file_name(id: &str) -> String {
+    format!("season-{id}.json")
+}
+
+impl Seasons {
+    /// Picks up the archives already in `dir`.
+    pub fn new(calendar: SeasonCalendar, dir: PathBuf) -> Self {
+        let archived = calendar
+            .seasons()
+            .iter()
+            .filter(|s| dir.join(file_name(&s.id)).exists())
+            .map(|s| s.id.clone())
+            .collect();
+        Self { calendar, dir, archived: RwLock::new(archived) }
+    }
+
+    pub fn from_env() -> Result<Self, String> {
+        Ok(Self::new(SeasonCalendar::from_env()?, archive_dir()))
+    }
+
+    pub fn calendar(&self) -> &SeasonCalendar {
+        &self.calendar
+    }
+
+    pub fn is_archived(&self, id: &str) -> bool {
+        self.archived.read().unwrap().contains(id)
+    }
+
+    /// The season an event at `t` counts toward. Call under the sessions
+    /// write lock so it cannot race an archive.
+    pub fn season_ref(&self, t: DateTime<Utc>) -> Option<SeasonRef> {
+        let season = self.calendar.at(t)?;
+        Some(SeasonRef { id: season.id.clone(), closed: self.is_archived(&season.id) })
+    }
+
+    pub fn read_archive(&self, id: &str) -> io::Result<Option<SeasonArchive>> {
+        match fs::read(self.dir.join(file_name(id))) {
+            Ok(bytes) => serde_json::from_slice(&bytes).map(Some).map_err(io::Error::other),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Freezes every season that ended before `now` and has no archive yet,
+    /// then unlocks season-rank achievements for its finishers. The season
+    /// is closed first, so nothing can score in it while its standings are
+    /// read. Returns the ids archived.
+    pub fn archive_ended(
+        &self,
+        store: &dyn SubmissionStore,
+        sessions: &Sessions,
+        tiers: &TierTable,
+        achievements: &AchievementBook,
+        now: DateTime<Utc>,
+    ) -> io::Result<Vec<String>> {
+        let due: Vec<&Season> = self
+            .calendar
+            .seasons()
+            .iter()
+            .filter(|s| s.ends_at <= now && !self.is_archived(&s.id))
+            .collect();
+
+        let mut done = Vec::new();
+        for season in due {
+            self.archived.write().unwrap().insert(season.id.clone());
+            // a writer holding the sessions lock finishes before we read
+            let standings = match self.write_archive(season, sessions, tiers, now) {
+                Ok(standings) => standings,
+                Err(e) => {
+                    self.archived.write().unwrap().remove(&season.id);
+                    return Err(e);
+                }
+            };
+            unlock_finishers(store, sessions, achievements, &season.id, &standings);
+            done.push(season.id.clone());
+        }
+        Ok(done)
+    }
+
+    fn write_archive(
+        &self,
+        season: &Season,
+        sessions: &Sessions,
+        tiers: &TierTable,
+        now: DateTime<Utc>,
+    ) -> io::Result<Vec<LeaderboardEntry>> {
+        let standings = build_leaderboard(sessions, tiers, Ranking::Season(season));
+        let archive = SeasonArchive { season: season.clone(), archived_at: now, standings };
+
+        fs::create_dir_all(&self.dir)?;
+        let path = self.dir.join(file_name(&season.id));
+        let tmp = path.with_extension("tmp");
+        {
+            let mut f = File::create(&tmp)?;
+            f.write_all(&serde_json::to_vec_pretty(&archive).map_err(io::Error::other)?)?;
+            f.sync_all()?;
+        }
+        fs::rename(&tmp, &path)?;
+        Ok(archive.standings)
+    }
+
+    /// Drops every archive (used when the log itself is wiped).
+    pub fn purge(&self) -> io::Result<()> {
+        let mut archived = self.archived.write().unwrap();
+        for id in archived.drain() {
+            match fs::remove_file(self.dir.join(file_name(&id))) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+}
+
+/// Season-rank achievements for everyone whose final rank earns one.
+/// Wallets left on 0 points (everything reverted) did not finish.
+fn unlock_finishers(
+    store: &dyn SubmissionStore,
+    sessions: &Sessions,
+    achievements: &AchievementBook,
+    season: &str,
+    standings: &[LeaderboardEntry],
+) {
+    let mut sessions = sessions.write();
+    let mut unlocked = 0;
+    for entry in standings {
+        let Some(pubkey) = entry.wallet.as_ref().filter(|_| entry.score > 0) else { continue };
+        let found: Vec<_> = achievements.for_season_rank(entry.rank).collect();
+        if found.is_empty() {
+            continue;
+        }
+        let wallet = hex::encode(pubkey.as_bytes());
+        let trigger = Trigger { wallet: &wallet, mu_level: 0, block_height: 0, season: Some(season) };
+        unlocked += unlock_and_log(store, &mut sessions, pubkey, &found, trigger).len();
+    }
+    if unlocked > 0 {
+        log_state_root(sessions.iter());
+    }
+}
+
+/// Background task: archive seasons as they end.
+pub fn spawn_timer(
+    seasons: Arc<Seasons>,
+    store: Arc<dyn SubmissionStore>,
+    sessions: Arc<Sessions>,
+    tiers: Arc<TierTable>,
+    achievements: Arc<AchievementBook>,
+    every: Duration,
+) {
+    tokio::spawn(async move {
+        let mut tick = tokio::time::interval(every);
+        loop {
+            tick.tick().await;
+            let (seasons, store, sessions, tiers, achievements) =
+                (seasons.clone(), store.clone(), sessions.clone(), tiers.clone(), achievements.clone());
+            let res = tokio::task::spawn_blocking(move || {
+                seasons.archive_ended(store.as_ref(), &sessions, &tiers, &achievements, Utc::now())
+            })
+            .await;
+            match res {
+                Ok(Ok(ids)) => {
+                    for id in ids {
+                        println!("🏆 Season '{id}' archived");
+                    }
+                }
+                Ok(Err(e)) => eprintln!("⚠️  Season archive failed: {e}"),
+                Err(e) => eprintln!("⚠️  Season archive task panicked: {e}"),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::extract::{Query, State};
+    use axum::http::StatusCode;
+
+    use crate::handlers::leaderboard::{get_leaderboard, LeaderboardQuery};
+    use crate::handlers::submission::AppState;
+    use crate::testutil::{app_state, mine, temp_dir, wallet};
+
+    /// Server state with season `s1` running now and `s2` after it.
+    fn with_seasons(dir: &Path) -> (AppState, Season) {
+        let now = Utc::now();
+        let (hour, path) = (chrono::Duration::hours(1), dir.join("seasons.json"));
+        let seasons = serde_json::json!({ "seasons": [
+            { "id": "s1", "starts_at": now - hour, "ends_at": now + hour },
+            { "id": "s2", "starts_at": now + hour, "ends_at": now + hour * 2 },
+        ]});
+        fs::write(&path, seasons.to_string()).unwrap();
+        let calendar = SeasonCalendar::load(&path).unwrap();
+        let s1 = calendar.get("s1").unwrap().clone();
+        let app = AppState {
+            seasons: Arc::new(Seasons::new(calendar, dir.join("seasons"))),
+            ..app_state(dir)
+        };
+        (app, s1)
+    }
+
+    fn archive(app: &AppState, at: DateTime<Utc>) -> Vec<String> {
+        app.seasons.archive_ended(app.store.as_ref(), &app.sessions, &app.tiers, &app.achievements, at).unwrap()
+    }
+
+    async fn season_board(app: &AppState, id: &str) -> Vec<LeaderboardEntry> {
+        let q = LeaderboardQuery { season: Some(id.into()), view: None };
+        let board = get_leaderboard(
+            State(app.sessions.clone()),
+            State(app.tiers.clone()),
+            State(app.seasons.clone()),
+            State(app.rules.clone()),
+            Query(q),
+        );
+        board.await.unwrap().0
+    }
+
+    #[tokio::test]
+    async fn ended_seasons_are_archived_once_and_closed() {
+        let dir = temp_dir("seasons-archive");
+        let (app, s1) = with_seasons(&dir);
+        let (a, b) = (wallet('a'), wallet('b'));
+        mine(&app, &a, None, 21, 5).await.unwrap();
+        mine(&app, &b, None, 18, 6).await.unwrap();
+        assert_eq!(app.store.by_wallet(&a).unwrap()[0].season.as_deref(), Some("s1"));
+
+        // not before its end, then exactly once
+        assert!(archive(&app, s1.ends_at - chrono::Duration::seconds(1)).is_empty());
+        assert_eq!(archive(&app, s1.ends_at), vec!["s1"]);
+        assert!(archive(&app, s1.ends_at).is_empty());
+        assert!(app.seasons.is_archived("s1") && !app.seasons.is_archived("s2"));
+
+        let frozen = app.seasons.read_archive("s1").unwrap().unwrap();
+        let scores: Vec<(usize, u32)> = frozen.standings.iter().map(|e| (e.rank, e.score)).collect();
+        assert_eq!(scores, vec![(1, 400), (2, 70)]);
+
+        // the wall clock is still inside s1, but it no longer takes events
+        let before = app.store.all().unwrap().len();
+        let err = mine(&app, &a, None, 18, 7).await.unwrap_err();
+        assert_eq!((err.status, err.body["error"].as_str()), (StatusCode::CONFLICT, Some("season_closed")));
+        assert_eq!(app.store.all().unwrap().len(), before);
+
+        // a restart picks the archive back up
+        let reopened = Seasons::new(app.seasons.calendar().clone(), dir.join("seasons"));
+        assert!(reopened.is_archived("s1"));
+    }
+
+    #[tokio::test]
+    async fn archived_season_leaderboard_is_served_from_its_file() {
+        let dir = temp_dir("seasons-board");
+        let (app, s1) = with_seasons(&dir);
+        mine(&app, &wallet('a'), None, 21, 5).await.unwrap();
+        mine(&app, &wallet('b'), None, 18, 6).await.unwrap();
+        assert_eq!(season_board(&app, "s1").await.len(), 2);
+        archive(&app, s1.ends_at);
+
+        // trim the file: the board follows it, not the live sessions
+        let path = dir.join("seasons").join(file_name("s1"));
+        let mut frozen = app.seasons.read_archive("s1").unwrap().unwrap();
+        frozen.standings.truncate(1);
+        fs::write(&path, serde_json::to_vec(&frozen).unwrap()).unwrap();
+        let board = season_board(&app, "s1").await;
+        assert_eq!(board.iter().map(|e| e.score).collect::<Vec<_>>(), vec![400]);
+
+        // a season that is not archived is ranked live
+        assert!(season_board(&app, "s2").await.is_empty());
+    }
+}
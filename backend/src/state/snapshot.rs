@@ -0,0 +1,333 @@
+// backend/src/state/snapshot.rs
+
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use blake2::{Blake2b512, Digest};
+use borsh::{BorshDeserialize, BorshSerialize};
+use chrono::Utc;
+
+use crate::engine::guild::GuildSession;
+use crate::engine::kdapp::MueHeroSession;
+use crate::state::{pki::PubKey, Guilds, Sessions};
+use crate::store::SubmissionStore;
+
+/// File layout: MAGIC | version (u16 LE) | borsh(SnapshotV11) | blake2b-512(everything before)
+///
+/// Borsh has no optional fields, so any new session state needs a new
+/// version. Older versions lack state added since (v1: the scored-block set,
+/// v2: its rollback points, v3: participants, v4: season scores, v5: daily
+/// points, v6: bonus state, v7: achievements, v8: guilds, v9: last-event
+/// metadata, v10: event times of scored blocks) and cannot be migrated;
+/// `load_latest` discards them, says so, and the sessions are rebuilt from
+/// the log.
+const MAGIC: &[u8; 8] = b"MUESNAP\0";
+const VERSION: u16 = 11;
+const CHECKSUM_LEN: usize = 64;
+const KEEP: usize = 3;
+
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct SnapshotV11 {
+    /// Store cursor the sessions are consistent with; replay resumes here.
+    pub cursor: u64,
+    pub created_at: i64,
+    /// Sorted by pubkey bytes so identical state encodes identically.
+    pub sessions: Vec<(PubKey, MueHeroSession)>,
+    /// Sorted by name.
+    pub guilds: Vec<(String, GuildSession)>,
+}
+
+/// `SNAPSHOT_DIR`, default `logs/snapshots`.
+pub fn snapshot_dir() -> PathBuf {
+    std::env::var("SNAPSHOT_DIR").unwrap_or_else(|_| "logs/snapshots".into()).into()
+}
+
+/// `SNAPSHOT_INTERVAL_SECS`, default 300; 0 turns the timer off.
+pub fn snapshot_interval() -> Option<Duration> {
+    let secs = std::env::var("SNAPSHOT_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .unwrap_or(300);
+    (secs > 0).then(|| Duration::from_secs(secs))
+}
+
+fn file_name(cursor: u64) -> String {
+    format!("snapshot-{cursor:020}.bin")
+}
+
+fn checksum(bytes: &[u8]) -> [u8; CHECKSUM_LEN] {
+    let mut out = [0u8; CHECKSUM_LEN];
+    out.copy_from_slice(&Blake2b512::digest(bytes));
+    out
+}
+
+fn encode(snap: &SnapshotV11) -> io::Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    buf.extend_from_slice(MAGIC);
+    buf.extend_from_slice(&VERSION.to_le_bytes());
+    snap.serialize(&mut buf)?;
+    let sum = checksum(&buf);
+    buf.extend_from_slice(&sum);
+    Ok(buf)
+}
+
+fn decode(bytes: &[u8]) -> io::Result<SnapshotV11> {
+    let bad = |msg: &str| io::Error::new(io::ErrorKind::InvalidData, msg.to_string());
+    if bytes.len() < MAGIC.len() + 2 + CHECKSUM_LEN || &bytes[..MAGIC.len()] != MAGIC {
+        return Err(bad("not a snapshot file"));
+    }
+    let (body, sum) = bytes.split_at(bytes.len() - CHECKSUM_LEN);
+    if checksum(body) != sum {
+        return Err(bad("checksum mismatch"));
+    }
+    let version = u16::from_le_bytes([body[MAGIC.len()], body[MAGIC.len() + 1]]);
+    match version {
+        VERSION => SnapshotV11::try_from_slice(&body[MAGIC.len() + 2..]),
+        v if v < VERSION => Err(bad(&format!("old snapshot version {v}, this build reads {VERSION}"))),
+        v => Err(bad(&format!("snapshot version {v} is newer than this build's {VERSION}"))),
+    }
+}
+
+/// Snapshot files in `dir`, newest (highest cursor) first.
+fn list(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = match fs::read_dir(dir) {
+        Ok(rd) => rd
+            .filter_map(|e| e.ok().map(|e| e.path()))
+            .filter(|p| {
+                p.file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.starts_with("snapshot-") && n.ends_with(".bin"))
+            })
+            .collect(),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => return Err(e),
+    };
+    // zero-padded cursor in the name, so lexical order == cursor order
+    files.sort();
+    files.reverse();
+    Ok(files)
+}
+
+/// Writes the current sessions and guilds and the store cursor they cover.
+/// Returns `None` when nothing changed since the last snapshot.
+pub fn write_snapshot(
+    dir: &Path,
+    store: &dyn SubmissionStore,
+    sessions: &Sessions,
+    guilds: &Guilds,
+) -> io::Result<Option<PathBuf>> {
+    let snap = {
+        // handlers append while holding the write lock, so under the read
+        // lock the sessions and the cursor describe the same prefix of the log
+        let sessions = sessions.read();
+        let cursor = store.cursor().map_err(|e| io::Error::other(e.to_string()))?;
+        let mut entries: Vec<(PubKey, MueHeroSession)> =
+            sessions.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+        entries.sort_by(|a, b| a.0.as_bytes().cmp(b.0.as_bytes()));
+        // guild writers hold the sessions write lock too, so this is the same prefix
+        let mut guild_entries: Vec<(String, GuildSession)> =
+            guilds.read().iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+        guild_entries.sort_by(|a, b| a.0.cmp(&b.0));
+        SnapshotV11 {
+            cursor,
+            created_at: Utc::now().timestamp(),
+            sessions: entries,
+            guilds: guild_entries,
+        }
+    };
+
+    fs::create_dir_all(dir)?;
+    let path = dir.join(file_name(snap.cursor));
+    if path.exists() {
+        return Ok(None);
+    }
+
+    // write-then-rename so a crash never leaves a half-written snapshot behind
+    let tmp = path.with_extension("tmp");
+    {
+        let mut f = File::create(&tmp)?;
+        f.write_all(&encode(&snap)?)?;
+        f.sync_all()?;
+    }
+    fs::rename(&tmp, &path)?;
+
+    for old in list(dir)?.into_iter().skip(KEEP) {
+        let _ = fs::remove_file(old);
+    }
+    Ok(Some(path))
+}
+
+/// Reads and checks one snapshot file.
+pub fn read_snapshot(path: &Path) -> io::Result<SnapshotV11> {
+    decode(&fs::read(path)?)
+}
+
+/// Newest snapshot that decodes cleanly and does not run past the store.
+pub fn load_latest(dir: &Path, store_cursor: u64) -> io::Result<Option<SnapshotV11>> {
+    for path in list(dir)? {
+        match read_snapshot(&path) {
+            Ok(snap) if snap.cursor <= store_cursor => return Ok(Some(snap)),
+            Ok(snap) => eprintln!(
+                "⚠️  Ignoring snapshot {} (cursor {} is past log end {})",
+                path.display(), snap.cursor, store_cursor
+            ),
+            Err(e) => eprintln!(
+                "⚠️  Discarding snapshot {} ({e}); rebuilding from the log instead",
+                path.display()
+            ),
+        }
+    }
+    Ok(None)
+}
+
+/// Replaces `sessions` and `guilds` with the snapshot contents.
+pub fn restore(sessions: &Sessions, guilds: &Guilds, snap: SnapshotV11) {
+    let map: HashMap<PubKey, MueHeroSession> = snap.sessions.into_iter().collect();
+    sessions.replace(map);
+    guilds.replace(snap.guilds.into_iter().collect());
+}
+
+/// Drops every snapshot (used when the log itself is wiped).
+pub fn purge(dir: &Path) -> io::Result<()> {
+    for path in list(dir)? {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+/// Background task: snapshot every `every`, skipping when nothing changed.
+pub fn spawn_timer(
+    dir: PathBuf,
+    store: Arc<dyn SubmissionStore>,
+    sessions: Arc<Sessions>,
+    guilds: Arc<Guilds>,
+    every: Duration,
+) {
+    tokio::spawn(async move {
+        let mut tick = tokio::time::interval(every);
+        tick.tick().await; // first tick fires immediately; nothing new to save yet
+        loop {
+            tick.tick().await;
+            let (dir, store, sessions, guilds) =
+                (dir.clone(), store.clone(), sessions.clone(), guilds.clone());
+            let res = tokio::task::spawn_blocking(move || {
+                write_snapshot(&dir, store.as_ref(), &sessions, &guilds)
+            })
+            .await;
+            match res {
+                Ok(Ok(Some(path))) => println!("📸 Snapshot written to {}", path.display()),
+                Ok(Ok(None)) => {}
+                Ok(Err(e)) => eprintln!("⚠️  Snapshot failed: {e}"),
+                Err(e) => eprintln!("⚠️  Snapshot task panicked: {e}"),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::{Path as UrlPath, State}, http::{HeaderMap, HeaderValue}, Json};
+
+    use crate::engine::season::SeasonCalendar;
+    use crate::handlers::guilds::{post_join, GuildReq};
+    use crate::handlers::submission::{replay_into_sessions, AppState};
+    use crate::state::root::{session_roots, state_root, Root};
+    use crate::testutil::{app_state, mine, temp_dir, wallet, SECRET};
+
+    fn root_of(sessions: &Sessions) -> Root {
+        state_root(&session_roots(sessions.read().iter()))
+    }
+
+    fn snapshot(app: &AppState, dir: &Path) -> io::Result<Option<PathBuf>> {
+        write_snapshot(dir, app.store.as_ref(), &app.sessions, &app.guilds)
+    }
+
+    #[tokio::test]
+    async fn restoring_a_snapshot_brings_back_sessions_and_guilds() {
+        let dir = temp_dir("snapshot-restore");
+        let app = app_state(&dir);
+        let a = wallet('a');
+        mine(&app, &a, None, 18, 3).await.unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert("X-MUE-KEY", HeaderValue::from_static(SECRET));
+        let req = Json(GuildReq { wallet: a.clone() });
+        let Json(joined) = post_join(State(app.clone()), headers, UrlPath("miners".into()), req).await.unwrap();
+        assert_eq!(joined["members"], 1);
+        mine(&app, &a, None, 17, 4).await.unwrap();
+
+        let snaps = dir.join("snapshots");
+        let path = snapshot(&app, &snaps).unwrap().expect("a first snapshot");
+        assert!(snapshot(&app, &snaps).unwrap().is_none(), "nothing changed, nothing written");
+
+        let snap = read_snapshot(&path).unwrap();
+        assert_eq!(snap.cursor, app.store.cursor().unwrap());
+        let (sessions, guilds) = (Sessions::new(), Guilds::new());
+        restore(&sessions, &guilds, snap);
+        assert_eq!(root_of(&sessions), root_of(&app.sessions));
+        let score = |g: &Guilds| g.read().get("miners").map(|s| s.score());
+        assert!(score(&guilds).is_some_and(|s| s > 0));
+        assert_eq!(score(&guilds), score(&app.guilds));
+    }
+
+    #[tokio::test]
+    async fn a_corrupted_snapshot_is_rejected_and_the_previous_one_used() {
+        let dir = temp_dir("snapshot-corrupt");
+        let app = app_state(&dir);
+        let (a, b) = (wallet('a'), wallet('b'));
+        let snaps = dir.join("snapshots");
+        mine(&app, &a, None, 18, 3).await.unwrap();
+        let older = snapshot(&app, &snaps).unwrap().unwrap();
+        mine(&app, &b, None, 18, 4).await.unwrap();
+        let newer = snapshot(&app, &snaps).unwrap().unwrap();
+
+        let mut bytes = fs::read(&newer).unwrap();
+        let mid = bytes.len() / 2;
+        bytes[mid] ^= 0xff;
+        fs::write(&newer, bytes).unwrap();
+        let err = read_snapshot(&newer).err().expect("a flipped byte fails the checksum");
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(err.to_string(), "checksum mismatch");
+        fs::write(snaps.join(file_name(u64::MAX)), b"short").unwrap();
+        assert_eq!(read_snapshot(&snaps.join(file_name(u64::MAX))).err().unwrap().to_string(), "not a snapshot file");
+
+        let cursor = app.store.cursor().unwrap();
+        let loaded = load_latest(&snaps, cursor).unwrap().expect("falls back to the older snapshot");
+        assert_eq!(loaded.cursor, read_snapshot(&older).unwrap().cursor);
+        // one past the log end is a snapshot of some other log
+        assert!(load_latest(&snaps, loaded.cursor - 1).unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn replaying_the_tail_after_a_snapshot_matches_the_live_state() {
+        let dir = temp_dir("snapshot-tail");
+        let app = app_state(&dir);
+        let (a, b) = (wallet('a'), wallet('b'));
+        let snaps = dir.join("snapshots");
+        mine(&app, &a, None, 18, 3).await.unwrap();
+        snapshot(&app, &snaps).unwrap().unwrap();
+        mine(&app, &b, None, 17, 4).await.unwrap();
+        mine(&app, &a, None, 16, 5).await.unwrap();
+
+        let snap = load_latest(&snaps, app.store.cursor().unwrap()).unwrap().unwrap();
+        let (cursor, sessions, guilds) = (snap.cursor, Sessions::new(), Guilds::new());
+        restore(&sessions, &guilds, snap);
+        assert_ne!(root_of(&sessions), root_of(&app.sessions));
+        let (rules, calendar) = (app.rules.current(), SeasonCalendar::default());
+        let report = replay_into_sessions(
+            app.store.as_ref(), &sessions, &guilds, &rules, &app.rules_history, &calendar, cursor,
+        )
+        .unwrap();
+        let mut tail = 0;
+        app.store.for_each_from(cursor, &mut |_| tail += 1).unwrap();
+        assert!(tail < app.store.all().unwrap().len());
+        assert_eq!(report.replayed, tail, "only the records after the snapshot");
+        assert_eq!(root_of(&sessions), root_of(&app.sessions));
+    }
+}
@@ -0,0 +1,262 @@
+// backend/src/store/batch.rs
+
+use std::{
+    fs::{create_dir_all, File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::engine::merkle::{compute_merkle_proof, compute_merkle_root, verify_merkle_proof};
+use crate::handlers::submission::Submission;
+use crate::store::chain::{record_hash, Link};
+use crate::store::jsonl::{encode_line, unframe_line};
+use crate::store::{StoreError, SubmissionStore};
+
+/// A closed run of consecutive records and the Merkle root over their hashes.
+/// Leaves are the chain hashes (`chain::record_hash`) of each record, in order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Batch {
+    pub id: u64,
+    pub first_seq: u64,
+    pub last_seq: u64,
+    pub root: String,
+    pub leaves: Vec<String>,
+    pub closed_at: String,
+}
+
+/// Everything needed to check one record with `verify_merkle_proof`.
+#[derive(Debug, Clone, Serialize)]
+pub struct InclusionProof {
+    pub seq: u64,
+    pub batch: u64,
+    pub index: usize,
+    /// Leaves in the batch; `verify_merkle_proof` needs it to know where
+    /// the odd node is promoted.
+    pub leaf_count: usize,
+    pub leaf: String,
+    pub siblings: Vec<String>,
+    pub root: String,
+    pub verified: bool,
+}
+
+struct BatchState {
+    file: File,
+    batches: Vec<Batch>,
+    pending: Vec<Link>,
+}
+
+/// Wraps another store and seals its records into Merkle batches every
+/// `size` appends; `spawn_timer` seals whatever is left on an interval.
+pub struct BatchingStore {
+    inner: Arc<dyn SubmissionStore>,
+    size: usize,
+    state: Mutex<BatchState>,
+}
+
+fn decode_hash(s: &str) -> Option<[u8; 64]> {
+    hex::decode(s).ok()?.try_into().ok()
+}
+
+fn load_batches(path: &Path) -> Result<Vec<Batch>, StoreError> {
+    let file = match File::open(path) {
+        Ok(f) => f,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut out = Vec::new();
+    for (i, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() { continue; }
+        match unframe_line(line.trim()).ok().and_then(|j| serde_json::from_str::<Batch>(j).ok()) {
+            Some(b) => out.push(b),
+            None => eprintln!("⚠️  {}: skipping corrupt batch on line {}", path.display(), i + 1),
+        }
+    }
+    Ok(out)
+}
+
+impl BatchingStore {
+    pub fn open(inner: Arc<dyn SubmissionStore>, path: &str, size: usize) -> Result<Self, StoreError> {
+        let path = PathBuf::from(path);
+        if let Some(dir) = path.parent() {
+            create_dir_all(dir)?;
+        }
+        let batches = load_batches(&path)?;
+
+        // records appended after the last sealed batch (e.g. before a crash)
+        let next = batches.last().map_or(0, |b| b.last_seq + 1);
+        let mut pending = Vec::new();
+        let mut seq = 0u64;
+        inner.for_each_raw(&mut |json| {
+            if seq >= next {
+                pending.push(Link { seq, hash: record_hash(json) });
+            }
+            seq += 1;
+        })?;
+
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        Ok(Self {
+            inner,
+            size: size.max(1),
+            state: Mutex::new(BatchState { file, batches, pending }),
+        })
+    }
+
+    /// `BATCH_SIZE` (default 64) and `BATCH_LOG_PATH` (default `logs/batches.jsonl`).
+    pub fn open_from_env(inner: Arc<dyn SubmissionStore>) -> Result<Self, StoreError> {
+        let size = std::env::var("BATCH_SIZE")
+            .ok()
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or(64);
+        let path = std::env::var("BATCH_LOG_PATH").unwrap_or_else(|_| "logs/batches.jsonl".into());
+        Self::open(inner, &path, size)
+    }
+
+    fn seal(&self, st: &mut BatchState, take: usize) -> Result<Option<Batch>, StoreError> {
+        if take == 0 { return Ok(None); }
+        let leaves: Vec<[u8; 64]> = st.pending[..take].iter().map(|l| l.hash).collect();
+        let batch = Batch {
+            id: st.batches.len() as u64,
+            first_seq: st.pending[0].seq,
+            last_seq: st.pending[take - 1].seq,
+            root: hex::encode(compute_merkle_root(leaves.clone())),
+            leaves: leaves.iter().map(hex::encode).collect(),
+            closed_at: Utc::now().to_rfc3339(),
+        };
+        let line = encode_line(&serde_json::to_string(&batch)?);
+        st.file.write_all(line.as_bytes())?;
+        st.file.sync_data()?;
+        st.pending.drain(..take);
+        st.batches.push(batch.clone());
+        Ok(Some(batch))
+    }
+
+    /// Seals every pending record into a batch, if there are any.
+    pub fn close_pending(&self) -> Result<Option<Batch>, StoreError> {
+        let mut st = self.state.lock().unwrap();
+        let take = st.pending.len();
+        self.seal(&mut st, take)
+    }
+
+    /// Inclusion proof for one record, or `None` if it is not sealed yet.
+    pub fn proof_for(&self, seq: u64) -> Option<InclusionProof> {
+        let st = self.state.lock().unwrap();
+        let pos = st.batches.partition_point(|b| b.last_seq < seq);
+        let batch = st.batches.get(pos).filter(|b| b.first_seq <= seq)?;
+        let index = (seq - batch.first_seq) as usize;
+
+        let leaves: Vec<[u8; 64]> = batch.leaves.iter().filter_map(|h| decode_hash(h)).collect();
+        let root = decode_hash(&batch.root)?;
+        let leaf = *leaves.get(index)?;
+        let leaf_count = leaves.len();
+        let siblings = compute_merkle_proof(leaves, index);
+        let verified = verify_merkle_proof(leaf, siblings.clone(), root, index, leaf_count);
+
+        Some(InclusionProof {
+            seq,
+            batch: batch.id,
+            index,
+            leaf_count,
+            leaf: hex::encode(leaf),
+            siblings: siblings.iter().map(hex::encode).collect(),
+            root: batch.root.clone(),
+            verified,
+        })
+    }
+}
+
+impl SubmissionStore for BatchingStore {
+    fn append(&self, sub: &Submission) -> Result<Link, StoreError> {
+        // hold our lock across the inner append so pending stays in seq order
+        let mut st = self.state.lock().unwrap();
+        let link = self.inner.append(sub)?;
+        st.pending.push(link);
+        while st.pending.len() >= self.size {
+            let take = self.size;
+            if let Err(e) = self.seal(&mut st, take) {
+                // the record itself is stored; the timer will retry the batch
+                eprintln!("⚠️  Failed to seal batch: {e}");
+                break;
+            }
+        }
+        Ok(link)
+    }
+
+    fn damage(&self) -> Vec<String> {
+        self.inner.damage()
+    }
+
+    fn by_wallet(&self, wallet: &str) -> Result<Vec<Submission>, StoreError> {
+        self.inner.by_wallet(wallet)
+    }
+
+    fn by_idempotency_key(&self, key: &str) -> Result<Option<Submission>, StoreError> {
+        self.inner.by_idempotency_key(key)
+    }
+
+    fn by_time_range(
+        &self,
+        after: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<Vec<Submission>, StoreError> {
+        self.inner.by_time_range(after, until)
+    }
+
+    fn cursor(&self) -> Result<u64, StoreError> {
+        self.inner.cursor()
+    }
+
+    fn for_each_from(&self, cursor: u64, f: &mut dyn FnMut(Submission)) -> Result<(), StoreError> {
+        self.inner.for_each_from(cursor, f)
+    }
+
+    fn for_each_raw(&self, f: &mut dyn FnMut(&str)) -> Result<(), StoreError> {
+        self.inner.for_each_raw(f)
+    }
+
+    fn clear(&self) -> Result<(), StoreError> {
+        let mut st = self.state.lock().unwrap();
+        self.inner.clear()?;
+        st.file.set_len(0)?;
+        st.file.sync_all()?;
+        st.batches.clear();
+        st.pending.clear();
+        Ok(())
+    }
+}
+
+/// `BATCH_INTERVAL_SECS`, default 60.
+pub fn batch_interval() -> Duration {
+    let secs = std::env::var("BATCH_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .filter(|s| *s > 0)
+        .unwrap_or(60);
+    Duration::from_secs(secs)
+}
+
+/// Background task: seal leftover records every `every`.
+pub fn spawn_timer(store: Arc<BatchingStore>, every: Duration) {
+    tokio::spawn(async move {
+        let mut tick = tokio::time::interval(every);
+        tick.tick().await;
+        loop {
+            tick.tick().await;
+            let store = store.clone();
+            match tokio::task::spawn_blocking(move || store.close_pending()).await {
+                Ok(Ok(Some(b))) => println!(
+                    "🌳 Sealed batch #{} (seq {}..={}, root {}…)",
+                    b.id, b.first_seq, b.last_seq, &b.root[..16]
+                ),
+                Ok(Ok(None)) => {}
+                Ok(Err(e)) => eprintln!("⚠️  Failed to seal batch: {e}"),
+                Err(e) => eprintln!("⚠️  Batch task panicked: {e}"),
+            }
+        }
+    });
+}
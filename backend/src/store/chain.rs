@@ -0,0 +1,207 @@
+// backend/src/store/chain.rs
+
+use blake2::{Blake2b512, Digest};
+use serde::Serialize;
+
+use crate::handlers::submission::Submission;
+use crate::store::{StoreError, SubmissionStore};
+
+/// `prev_hash` of the very first record.
+pub const GENESIS: [u8; 64] = [0u8; 64];
+
+/// Blake2b-512 over the record's stored JSON text (checksum framing excluded).
+/// Hashing the exact bytes keeps old links valid even if `Submission` grows.
+pub fn record_hash(json: &str) -> [u8; 64] {
+    let mut out = [0u8; 64];
+    out.copy_from_slice(&Blake2b512::digest(json.as_bytes()));
+    out
+}
+
+/// Position and hash of a freshly appended record.
+#[derive(Clone, Copy, Debug)]
+pub struct Link {
+    pub seq: u64,
+    pub hash: [u8; 64],
+}
+
+/// Where the next append attaches. Backends keep one behind their write lock.
+#[derive(Clone, Copy, Debug)]
+pub struct ChainTail {
+    pub next_seq: u64,
+    pub last_hash: [u8; 64],
+}
+
+impl ChainTail {
+    pub fn genesis() -> Self {
+        Self { next_seq: 0, last_hash: GENESIS }
+    }
+
+    /// Stamps `seq`/`prev_hash` onto a copy of `sub` and returns it with its
+    /// JSON text. Call `advance` once that text is durably stored.
+    pub fn link(&self, sub: &Submission) -> Result<(Submission, String), StoreError> {
+        let mut rec = sub.clone();
+        rec.seq = Some(self.next_seq);
+        rec.prev_hash = Some(hex::encode(self.last_hash));
+        let json = serde_json::to_string(&rec)?;
+        Ok((rec, json))
+    }
+
+    pub fn advance(&mut self, json: &str) -> Link {
+        let link = Link { seq: self.next_seq, hash: record_hash(json) };
+        self.next_seq += 1;
+        self.last_hash = link.hash;
+        link
+    }
+}
+
+/// Rebuilds the tail by hashing every stored record; used when a store opens.
+pub fn scan_tail(store: &dyn SubmissionStore) -> Result<ChainTail, StoreError> {
+    let mut tail = ChainTail::genesis();
+    store.for_each_raw(&mut |json| { tail.advance(json); })?;
+    Ok(tail)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BrokenLink {
+    /// 0-based position of the offending record in the log.
+    pub position: u64,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChainReport {
+    pub records: u64,
+    /// Hash of the last record; publish it to pin the current log.
+    pub head: String,
+    pub first_broken: Option<BrokenLink>,
+}
+
+/// Walks the log from the start and reports the first broken link.
+///
+/// Records written before chaining existed carry no `seq`; they are accepted
+/// only as a prefix and are still folded into the hash chain.
+pub fn verify_chain(store: &dyn SubmissionStore) -> Result<ChainReport, StoreError> {
+    let mut tail = ChainTail::genesis();
+    let mut chained = false;
+    let mut first_broken: Option<BrokenLink> = None;
+
+    store.for_each_raw(&mut |json| {
+        let position = tail.next_seq;
+        if first_broken.is_none() {
+            let reason = match serde_json::from_str::<Submission>(json) {
+                Err(e) => Some(format!("unreadable record: {e}")),
+                Ok(rec) => match (rec.seq, rec.prev_hash.as_deref()) {
+                    (None, _) if chained => Some("unchained record after chain start".into()),
+                    (None, _) => None,
+                    (Some(seq), _) if seq != position => {
+                        Some(format!("seq {seq} where {position} was expected"))
+                    }
+                    (Some(_), prev) if prev != Some(hex::encode(tail.last_hash).as_str()) => {
+                        Some("prev_hash does not match previous record".into())
+                    }
+                    (Some(_), _) => { chained = true; None }
+                },
+            };
+            if let Some(reason) = reason {
+                first_broken = Some(BrokenLink { position, reason });
+            }
+        }
+        tail.advance(json);
+    })?;
+
+    Ok(ChainReport { records: tail.next_seq, head: hex::encode(tail.last_hash), first_broken })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::store::jsonl::{encode_line, unframe_line, JsonlStore, SegmentLimits};
+    use crate::testutil::{mined, temp_dir, wallet};
+
+    const LIMITS: SegmentLimits = SegmentLimits { max_bytes: u64::MAX, max_age_secs: 0, compress: false };
+
+    fn log_of(dir: &std::path::Path, records: u64) -> JsonlStore {
+        let store = JsonlStore::open(dir.join("s.jsonl").to_str().unwrap(), LIMITS).unwrap();
+        for h in 1..=records {
+            store.append(&mined(&wallet('a'), h, 10)).unwrap();
+        }
+        store
+    }
+
+    /// Rewrites record `n` (0-based) of the only segment through `edit`,
+    /// keeping a valid line checksum so only the chain can notice.
+    fn tamper(dir: &std::path::Path, n: usize, edit: impl Fn(&str) -> String) {
+        let path = dir.join("s-000001.jsonl");
+        let text = fs::read_to_string(&path).unwrap();
+        let out: String = text
+            .lines()
+            .enumerate()
+            .map(|(i, line)| {
+                let json = unframe_line(line).unwrap();
+                if i == n { encode_line(&edit(json)) } else { format!("{line}\n") }
+            })
+            .collect();
+        fs::write(&path, out).unwrap();
+    }
+
+    #[test]
+    fn intact_log_verifies_to_its_head() {
+        let dir = temp_dir("chain-intact");
+        let store = log_of(&dir, 3);
+        let last = store.append(&mined(&wallet('b'), 4, 25)).unwrap();
+
+        let report = verify_chain(&store).unwrap();
+        assert_eq!(report.records, 4);
+        assert_eq!(report.head, hex::encode(last.hash));
+        assert!(report.first_broken.is_none());
+        assert_eq!(scan_tail(&store).unwrap().next_seq, 4);
+    }
+
+    #[test]
+    fn edited_record_breaks_the_next_link() {
+        let dir = temp_dir("chain-edit");
+        drop(log_of(&dir, 4));
+        tamper(&dir, 1, |json| json.replace("\"score\":10", "\"score\":99"));
+
+        let store = JsonlStore::open_read_only(dir.join("s.jsonl").to_str().unwrap()).unwrap();
+        let broken = verify_chain(&store).unwrap().first_broken.expect("chain is broken");
+        assert_eq!(broken.position, 2);
+        assert_eq!(broken.reason, "prev_hash does not match previous record");
+    }
+
+    #[test]
+    fn reports_only_the_first_broken_link() {
+        let dir = temp_dir("chain-seq");
+        drop(log_of(&dir, 4));
+        tamper(&dir, 1, |json| json.replace("\"seq\":1", "\"seq\":7"));
+        tamper(&dir, 3, |json| json.replace("\"score\":10", "\"score\":1"));
+
+        let store = JsonlStore::open_read_only(dir.join("s.jsonl").to_str().unwrap()).unwrap();
+        let report = verify_chain(&store).unwrap();
+        assert_eq!(report.records, 4);
+        let broken = report.first_broken.expect("chain is broken");
+        assert_eq!(broken.position, 1);
+        assert_eq!(broken.reason, "seq 7 where 1 was expected");
+    }
+
+    #[test]
+    fn unchained_records_are_only_accepted_as_a_prefix() {
+        let dir = temp_dir("chain-legacy");
+        let legacy = serde_json::to_string(&mined(&wallet('c'), 1, 5)).unwrap();
+        fs::write(dir.join("s.jsonl"), format!("{legacy}\n")).unwrap();
+        let store = JsonlStore::open(dir.join("s.jsonl").to_str().unwrap(), LIMITS).unwrap();
+        store.append(&mined(&wallet('a'), 2, 10)).unwrap();
+        assert!(verify_chain(&store).unwrap().first_broken.is_none());
+        drop(store);
+
+        let mut text = fs::read_to_string(dir.join("s.jsonl")).unwrap();
+        text.push_str(&format!("{legacy}\n"));
+        fs::write(dir.join("s.jsonl"), text).unwrap();
+        let store = JsonlStore::open_read_only(dir.join("s.jsonl").to_str().unwrap()).unwrap();
+        let broken = verify_chain(&store).unwrap().first_broken.expect("chain is broken");
+        assert_eq!(broken.position, 2);
+        assert_eq!(broken.reason, "unchained record after chain start");
+    }
+}
@@ -0,0 +1,729 @@
+// backend/src/store/jsonl.rs
+
+use std::{
+    collections::HashMap,
+    fs::{self, create_dir_all, File, OpenOptions},
+    io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use chrono::{DateTime, Utc};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+
+use crate::handlers::submission::Submission;
+use crate::store::chain::{self, ChainTail, Link};
+use crate::store::{StoreError, SubmissionStore};
+
+/// Append-only JSONL log, one `Submission` per line, split into numbered
+/// segments (`submissions-000001.jsonl`, ...) listed in a manifest next to
+/// them. Only the last segment is written to; sealed ones never change and
+/// may be gzipped. Queries scan every segment in order.
+///
+/// Each line is framed as `<json>\t<crc32 hex>`; lines without the suffix are
+/// legacy records written before framing and are accepted as-is.
+pub struct JsonlStore {
+    dir: PathBuf,
+    stem: String,
+    limits: SegmentLimits,
+    /// Corrupt lines found at open; they are skipped by every read.
+    damage: Vec<String>,
+    writer: Arc<Mutex<Writer>>,
+}
+
+struct Writer {
+    file: File,
+    tail: ChainTail,
+    /// Records that carry an `Idempotency-Key`, by key.
+    idempotent: HashMap<String, Submission>,
+    manifest: Manifest,
+    /// Bytes and records in the active (last) segment.
+    active_len: u64,
+    active_records: u64,
+}
+
+/// When the active segment is sealed and a new one started.
+#[derive(Debug, Clone, Copy)]
+pub struct SegmentLimits {
+    pub max_bytes: u64,
+    /// 0 disables the age limit.
+    pub max_age_secs: u64,
+    /// Gzip segments once they are sealed.
+    pub compress: bool,
+}
+
+impl SegmentLimits {
+    /// `SEGMENT_MAX_BYTES` (default 64 MiB), `SEGMENT_MAX_AGE_SECS` (default
+    /// 86400, 0 = off) and `SEGMENT_COMPRESS` (default off).
+    pub fn from_env() -> Self {
+        let num = |key: &str, default: u64| {
+            std::env::var(key).ok().and_then(|v| v.trim().parse().ok()).unwrap_or(default)
+        };
+        let compress = std::env::var("SEGMENT_COMPRESS")
+            .is_ok_and(|v| matches!(v.trim().to_lowercase().as_str(), "1" | "true" | "yes"));
+        Self {
+            max_bytes: num("SEGMENT_MAX_BYTES", 64 * 1024 * 1024).max(1),
+            max_age_secs: num("SEGMENT_MAX_AGE_SECS", 86_400),
+            compress,
+        }
+    }
+}
+
+/// One entry of the manifest. Offsets are in uncompressed bytes, so a store
+/// cursor stays valid when a segment is compressed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Segment {
+    pub id: u64,
+    pub file: String,
+    /// Global offset of the segment's first byte.
+    pub start: u64,
+    /// Length once sealed; the active segment's length is the file length.
+    pub bytes: u64,
+    pub records: u64,
+    pub created_at: i64,
+    pub sealed: bool,
+    pub compressed: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Manifest {
+    pub segments: Vec<Segment>,
+}
+
+/// What `recover` found (and fixed) in a JSONL log.
+#[derive(Debug, Default, Clone)]
+pub struct RecoveryReport {
+    pub records: usize,
+    pub legacy: usize,
+    /// 1-based line numbers that failed their checksum or did not parse.
+    pub corrupt_lines: Vec<usize>,
+    /// Bytes cut from an unterminated final line.
+    pub truncated_bytes: u64,
+}
+
+#[derive(Debug)]
+pub enum LineError {
+    Checksum,
+    Parse,
+}
+
+pub fn encode_line(json: &str) -> String {
+    let crc = crc32fast::hash(json.as_bytes());
+    format!("{json}\t{crc:08x}\n")
+}
+
+/// Strips and checks the frame of one line (without its newline). Compact
+/// JSON never contains a raw tab, so the last tab separates the checksum.
+pub fn unframe_line(line: &str) -> Result<&str, LineError> {
+    match line.rsplit_once('\t') {
+        Some((json, crc)) => {
+            let want = u32::from_str_radix(crc.trim(), 16).map_err(|_| LineError::Checksum)?;
+            if crc32fast::hash(json.as_bytes()) != want {
+                return Err(LineError::Checksum);
+            }
+            Ok(json)
+        }
+        None => Ok(line),
+    }
+}
+
+pub fn decode_line(line: &str) -> Result<Submission, LineError> {
+    serde_json::from_str(unframe_line(line)?).map_err(|_| LineError::Parse)
+}
+
+fn tally(bytes: &[u8]) -> RecoveryReport {
+    let mut report = RecoveryReport::default();
+    for (i, raw) in bytes.split(|b| *b == b'\n').enumerate() {
+        let line = String::from_utf8_lossy(raw);
+        let line = line.trim_end_matches('\r');
+        if line.trim().is_empty() { continue; }
+        match decode_line(line) {
+            Ok(_) => {
+                report.records += 1;
+                if !line.contains('\t') { report.legacy += 1; }
+            }
+            Err(_) => report.corrupt_lines.push(i + 1),
+        }
+    }
+    report
+}
+
+/// Scans one segment, truncates an unterminated (torn) final line, and
+/// reports corrupt lines by number instead of dropping them silently.
+pub fn recover(path: &Path) -> Result<RecoveryReport, StoreError> {
+    let mut file = match OpenOptions::new().read(true).write(true).open(path) {
+        Ok(f) => f,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(RecoveryReport::default()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes)?;
+
+    // anything after the last '\n' was never acknowledged to a client
+    let intact = bytes.iter().rposition(|b| *b == b'\n').map_or(0, |i| i + 1);
+    let mut report = tally(&bytes[..intact]);
+    if intact < bytes.len() {
+        report.truncated_bytes = (bytes.len() - intact) as u64;
+        file.set_len(intact as u64)?;
+        file.sync_all()?;
+    }
+    Ok(report)
+}
+
+/// Prints what `recover`/`tally` found; corrupt lines are also kept in `damage`.
+fn warn_report(path: &Path, report: &RecoveryReport, damage: &mut Vec<String>) {
+    if report.truncated_bytes > 0 {
+        eprintln!(
+            "⚠️  {}: truncated {} bytes of torn final line",
+            path.display(), report.truncated_bytes
+        );
+    }
+    if !report.corrupt_lines.is_empty() {
+        let msg = format!("{}: skipping corrupt lines {:?}", path.display(), report.corrupt_lines);
+        eprintln!("⚠️  {msg}");
+        damage.push(msg);
+    }
+}
+
+/// Reader over a segment's uncompressed bytes, positioned `skip` bytes in.
+fn open_segment(path: &Path, compressed: bool, skip: u64) -> io::Result<Box<dyn BufRead>> {
+    segment_reader(File::open(path)?, compressed, skip)
+}
+
+/// `open_segment` over a file that is already open.
+fn segment_reader(mut file: File, compressed: bool, skip: u64) -> io::Result<Box<dyn BufRead>> {
+    if compressed {
+        let mut reader = BufReader::new(GzDecoder::new(file));
+        io::copy(&mut (&mut reader).take(skip), &mut io::sink())?;
+        Ok(Box::new(reader))
+    } else {
+        file.seek(SeekFrom::Start(skip))?;
+        Ok(Box::new(BufReader::new(file)))
+    }
+}
+
+fn save_manifest(path: &Path, manifest: &Manifest) -> io::Result<()> {
+    let tmp = path.with_extension("tmp");
+    {
+        let mut f = File::create(&tmp)?;
+        f.write_all(serde_json::to_string_pretty(manifest)?.as_bytes())?;
+        f.sync_all()?;
+    }
+    fs::rename(&tmp, path)
+}
+
+/// Gzips a sealed segment, points the manifest at the `.gz`, then removes
+/// the plain file. Runs off the request path; readers only ever see one of
+/// the two files through the manifest.
+fn compress_segment(dir: &Path, manifest_path: &Path, writer: &Mutex<Writer>, id: u64) -> io::Result<()> {
+    let plain = {
+        let w = writer.lock().unwrap();
+        match w.manifest.segments.iter().find(|s| s.id == id && s.sealed && !s.compressed) {
+            Some(seg) => seg.file.clone(),
+            None => return Ok(()),
+        }
+    };
+    let gz = format!("{plain}.gz");
+    let tmp = dir.join(format!("{gz}.tmp"));
+    {
+        let mut enc = GzEncoder::new(File::create(&tmp)?, Compression::default());
+        io::copy(&mut File::open(dir.join(&plain))?, &mut enc)?;
+        enc.finish()?.sync_all()?;
+    }
+    fs::rename(&tmp, dir.join(&gz))?;
+
+    let mut w = writer.lock().unwrap();
+    let Some(seg) = w.manifest.segments.iter_mut().find(|s| s.id == id && s.file == plain) else {
+        // the log was cleared meanwhile
+        let _ = fs::remove_file(dir.join(&gz));
+        return Ok(());
+    };
+    seg.file = gz;
+    seg.compressed = true;
+    save_manifest(manifest_path, &w.manifest)?;
+    drop(w);
+    fs::remove_file(dir.join(plain))
+}
+
+impl JsonlStore {
+    /// `path` names the log, e.g. `logs/submissions.jsonl`; segments and the
+    /// manifest (`submissions.manifest.json`) live in the same directory. A
+    /// pre-existing single-file log is adopted as segment 0.
+    pub fn open(path: &str, limits: SegmentLimits) -> Result<Self, StoreError> {
+        Self::open_with(path, limits, true)
+    }
+
+    /// Opens an existing log for reading only: nothing is repaired, created
+    /// or compressed, and appends fail. Used by the offline admin tools.
+    pub fn open_read_only(path: &str) -> Result<Self, StoreError> {
+        let limits = SegmentLimits { max_bytes: u64::MAX, max_age_secs: 0, compress: false };
+        Self::open_with(path, limits, false)
+    }
+
+    fn open_with(path: &str, limits: SegmentLimits, writable: bool) -> Result<Self, StoreError> {
+        let path = PathBuf::from(path);
+        let dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        if writable {
+            create_dir_all(if dir.as_os_str().is_empty() { Path::new(".") } else { &dir })?;
+        }
+        let stem = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .ok_or_else(|| StoreError::Config(format!("bad log path {}", path.display())))?
+            .to_string();
+
+        let manifest_path = dir.join(format!("{stem}.manifest.json"));
+        let mut manifest: Manifest = match fs::read(&manifest_path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Manifest::default(),
+            Err(e) => return Err(e.into()),
+        };
+        if manifest.segments.is_empty() {
+            let legacy = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+            let (id, file) = if path.exists() {
+                (0, legacy.to_string())
+            } else {
+                (1, format!("{stem}-{:06}.jsonl", 1))
+            };
+            manifest.segments.push(new_segment(id, file, 0));
+            if writable {
+                save_manifest(&manifest_path, &manifest)?;
+            }
+        }
+
+        // sealed segments are immutable; just report anything that rotted
+        let mut damage = Vec::new();
+        for seg in manifest.segments.iter().filter(|s| s.sealed) {
+            let seg_path = dir.join(&seg.file);
+            let mut bytes = Vec::new();
+            open_segment(&seg_path, seg.compressed, 0)?.read_to_end(&mut bytes)?;
+            warn_report(&seg_path, &tally(&bytes), &mut damage);
+            if writable && seg.compressed {
+                // left behind if we crashed right after compressing
+                let _ = fs::remove_file(dir.join(seg.file.trim_end_matches(".gz")));
+            }
+        }
+
+        let active = manifest.segments.last().expect("manifest has a segment");
+        let active_path = dir.join(&active.file);
+        let (report, file) = if writable {
+            let report = recover(&active_path)?;
+            (report, OpenOptions::new().create(true).append(true).open(&active_path)?)
+        } else {
+            let mut bytes = Vec::new();
+            let mut file = File::open(&active_path)?;
+            file.read_to_end(&mut bytes)?;
+            (tally(&bytes), file)
+        };
+        warn_report(&active_path, &report, &mut damage);
+
+        let active_len = file.metadata()?.len();
+        let pending: Vec<u64> = manifest
+            .segments
+            .iter()
+            .filter(|s| s.sealed && !s.compressed)
+            .map(|s| s.id)
+            .collect();
+
+        let store = Self {
+            dir,
+            stem,
+            limits,
+            damage,
+            writer: Arc::new(Mutex::new(Writer {
+                file,
+                tail: ChainTail::genesis(),
+                idempotent: HashMap::new(),
+                manifest,
+                active_len,
+                active_records: report.records as u64,
+            })),
+        };
+        let tail = chain::scan_tail(&store)?;
+        let mut idempotent = HashMap::new();
+        store.for_each(&mut |s| {
+            if let Some(key) = s.idempotency_key.clone() {
+                idempotent.insert(key, s);
+            }
+        })?;
+        {
+            let mut w = store.writer.lock().unwrap();
+            w.tail = tail;
+            w.idempotent = idempotent;
+        }
+
+        if writable && limits.compress {
+            for id in pending {
+                store.spawn_compress(id);
+            }
+        }
+        Ok(store)
+    }
+
+    fn manifest_path(&self) -> PathBuf {
+        self.dir.join(format!("{}.manifest.json", self.stem))
+    }
+
+    fn spawn_compress(&self, id: u64) {
+        let (dir, manifest_path, writer) = (self.dir.clone(), self.manifest_path(), self.writer.clone());
+        std::thread::spawn(move || {
+            if let Err(e) = compress_segment(&dir, &manifest_path, &writer, id) {
+                eprintln!("⚠️  Failed to compress segment {id}: {e}");
+            }
+        });
+    }
+
+    fn needs_roll(&self, w: &Writer) -> bool {
+        let seg = w.manifest.segments.last().expect("manifest has a segment");
+        let age = Utc::now().timestamp() - seg.created_at;
+        w.active_records > 0
+            && (w.active_len >= self.limits.max_bytes
+                || (self.limits.max_age_secs > 0 && age >= self.limits.max_age_secs as i64))
+    }
+
+    /// Seals the active segment and starts the next one. The manifest is
+    /// written before `w` changes, so a failure leaves the old segment active.
+    fn roll(&self, w: &mut Writer) -> Result<u64, StoreError> {
+        w.file.sync_all()?;
+        let mut manifest = w.manifest.clone();
+        let sealed = manifest.segments.last_mut().expect("manifest has a segment");
+        sealed.sealed = true;
+        sealed.bytes = w.active_len;
+        sealed.records = w.active_records;
+        let (sealed_id, next_start) = (sealed.id, sealed.start + sealed.bytes);
+
+        let next_id = sealed_id + 1;
+        let next = new_segment(next_id, format!("{}-{next_id:06}.jsonl", self.stem), next_start);
+        let file = OpenOptions::new().create(true).append(true).open(self.dir.join(&next.file))?;
+        manifest.segments.push(next);
+        save_manifest(&self.manifest_path(), &manifest)?;
+
+        w.file = file;
+        w.manifest = manifest;
+        w.active_len = 0;
+        w.active_records = 0;
+        Ok(sealed_id)
+    }
+
+    /// Visits each intact line after global offset `cursor`, across segments,
+    /// with its record JSON (frame stripped).
+    fn for_each_line_from(&self, cursor: u64, f: &mut dyn FnMut(&str)) -> Result<(), StoreError> {
+        // Open the segments and note their lengths under the writer lock,
+        // then read without it so appends are not held up by a long scan.
+        // Reading stops at the noted length, so a line appended meanwhile
+        // is never seen half-written, and an open handle still reads a
+        // plain segment that compression removes meanwhile.
+        let segments = {
+            let w = self.writer.lock().unwrap();
+            let last = w.manifest.segments.len() - 1;
+            let mut open = Vec::new();
+            for (i, seg) in w.manifest.segments.iter().enumerate() {
+                let len = if i == last { w.active_len } else { seg.bytes };
+                if cursor >= seg.start + len { continue; }
+                let skip = cursor.saturating_sub(seg.start);
+                open.push((File::open(self.dir.join(&seg.file))?, seg.compressed, skip, len - skip));
+            }
+            open
+        };
+        for (file, compressed, skip, len) in segments {
+            let reader = segment_reader(file, compressed, skip)?.take(len);
+            for line in reader.lines() {
+                let line = line?;
+                let line = line.trim();
+                if line.is_empty() { continue; }
+                // corrupt lines were already reported at open
+                if let Ok(json) = unframe_line(line) {
+                    f(json);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn scan(&self, keep: &dyn Fn(&Submission) -> bool) -> Result<Vec<Submission>, StoreError> {
+        let mut out = Vec::new();
+        self.for_each(&mut |s| if keep(&s) { out.push(s) })?;
+        Ok(out)
+    }
+}
+
+fn new_segment(id: u64, file: String, start: u64) -> Segment {
+    Segment {
+        id,
+        file,
+        start,
+        bytes: 0,
+        records: 0,
+        created_at: Utc::now().timestamp(),
+        sealed: false,
+        compressed: false,
+    }
+}
+
+impl SubmissionStore for JsonlStore {
+    fn append(&self, sub: &Submission) -> Result<Link, StoreError> {
+        let mut w = self.writer.lock().unwrap();
+        let (rec, json) = w.tail.link(sub)?;
+        let line = encode_line(&json);
+        // one write per record, then fsync before the caller reports success
+        let res = w.file.write_all(line.as_bytes()).and_then(|_| w.file.sync_data());
+        if let Err(e) = res {
+            // don't leave a partial frame for the next append to glue onto
+            let before = w.active_len;
+            let _ = w.file.set_len(before);
+            return Err(e.into());
+        }
+        w.active_len += line.len() as u64;
+        w.active_records += 1;
+        let link = w.tail.advance(&json);
+        if let Some(key) = rec.idempotency_key.clone() {
+            w.idempotent.insert(key, rec);
+        }
+
+        if self.needs_roll(&w) {
+            // the record itself is stored; a failed roll is retried next append
+            match self.roll(&mut w) {
+                Ok(id) if self.limits.compress => self.spawn_compress(id),
+                Ok(_) => {}
+                Err(e) => eprintln!("⚠️  Failed to roll log segment: {e}"),
+            }
+        }
+        Ok(link)
+    }
+
+    fn damage(&self) -> Vec<String> {
+        self.damage.clone()
+    }
+
+    fn by_wallet(&self, wallet: &str) -> Result<Vec<Submission>, StoreError> {
+        self.scan(&|s| s.wallet == wallet)
+    }
+
+    fn by_idempotency_key(&self, key: &str) -> Result<Option<Submission>, StoreError> {
+        Ok(self.writer.lock().unwrap().idempotent.get(key).cloned())
+    }
+
+    fn by_time_range(
+        &self,
+        after: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<Vec<Submission>, StoreError> {
+        self.scan(&|s| {
+            after.is_none_or(|a| s.date_mined > a) && until.is_none_or(|u| s.date_mined <= u)
+        })
+    }
+
+    fn cursor(&self) -> Result<u64, StoreError> {
+        let w = self.writer.lock().unwrap();
+        let active = w.manifest.segments.last().expect("manifest has a segment");
+        Ok(active.start + w.active_len)
+    }
+
+    fn for_each_from(&self, cursor: u64, f: &mut dyn FnMut(Submission)) -> Result<(), StoreError> {
+        self.for_each_line_from(cursor, &mut |json| {
+            if let Ok(s) = serde_json::from_str(json) {
+                f(s);
+            }
+        })
+    }
+
+    fn for_each_raw(&self, f: &mut dyn FnMut(&str)) -> Result<(), StoreError> {
+        self.for_each_line_from(0, f)
+    }
+
+    fn clear(&self) -> Result<(), StoreError> {
+        let mut w = self.writer.lock().unwrap();
+        let first = new_segment(1, format!("{}-{:06}.jsonl", self.stem, 1), 0);
+        let file = OpenOptions::new().create(true).append(true).open(self.dir.join(&first.file))?;
+        file.set_len(0)?;
+        for seg in w.manifest.segments.iter().filter(|s| s.file != first.file) {
+            let _ = fs::remove_file(self.dir.join(&seg.file));
+        }
+        w.manifest = Manifest { segments: vec![first] };
+        save_manifest(&self.manifest_path(), &w.manifest)?;
+        file.sync_all()?;
+        w.file = file;
+        w.active_len = 0;
+        w.active_records = 0;
+        w.tail = ChainTail::genesis();
+        w.idempotent.clear();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::store::chain::verify_chain;
+    use crate::testutil::{mined, temp_dir, wallet};
+
+    const NO_ROLL: SegmentLimits = SegmentLimits { max_bytes: u64::MAX, max_age_secs: 0, compress: false };
+
+    fn open(dir: &Path, limits: SegmentLimits) -> JsonlStore {
+        JsonlStore::open(dir.join("s.jsonl").to_str().unwrap(), limits).unwrap()
+    }
+
+    fn heights(store: &JsonlStore) -> Vec<u64> {
+        let mut heights = Vec::new();
+        store.for_each(&mut |s| heights.push(s.block_height)).unwrap();
+        heights
+    }
+
+    #[test]
+    fn recover_truncates_a_torn_final_line() {
+        let dir = temp_dir("jsonl-recover");
+        let path = dir.join("log.jsonl");
+        let whole = encode_line(&serde_json::to_string(&mined(&wallet('a'), 1, 10)).unwrap());
+        let torn = "{\"wallet\":\"bb";
+        fs::write(&path, format!("{whole}{torn}")).unwrap();
+
+        let report = recover(&path).unwrap();
+        assert_eq!(report.records, 1);
+        assert_eq!(report.truncated_bytes, torn.len() as u64);
+        assert!(report.corrupt_lines.is_empty());
+        assert_eq!(fs::read_to_string(&path).unwrap(), whole);
+
+        // nothing left to cut the second time
+        assert_eq!(recover(&path).unwrap().truncated_bytes, 0);
+    }
+
+    #[test]
+    fn reports_corrupt_lines_by_number() {
+        let dir = temp_dir("jsonl-corrupt");
+        let path = dir.join("log.jsonl");
+        let good = encode_line(&serde_json::to_string(&mined(&wallet('a'), 1, 10)).unwrap());
+        let bad = good.replace("\"score\":10", "\"score\":11");
+        fs::write(&path, format!("{good}{bad}{good}")).unwrap();
+
+        let report = recover(&path).unwrap();
+        assert_eq!(report.records, 2);
+        assert_eq!(report.corrupt_lines, vec![2]);
+        assert_eq!(report.truncated_bytes, 0);
+    }
+
+    #[test]
+    fn reopening_after_a_torn_append_keeps_the_chain() {
+        let dir = temp_dir("jsonl-torn");
+        let store = open(&dir, NO_ROLL);
+        store.append(&mined(&wallet('a'), 1, 10)).unwrap();
+        store.append(&mined(&wallet('a'), 2, 10)).unwrap();
+        drop(store);
+
+        let segment = dir.join("s-000001.jsonl");
+        let mut file = OpenOptions::new().append(true).open(&segment).unwrap();
+        file.write_all(b"{\"wallet\":\"aaaa\",\"sco").unwrap();
+        drop(file);
+
+        let store = open(&dir, NO_ROLL);
+        assert!(store.damage().is_empty());
+        assert_eq!(heights(&store), vec![1, 2]);
+        assert!(fs::read_to_string(&segment).unwrap().ends_with('\n'));
+
+        // the next append attaches to the last intact record
+        assert_eq!(store.append(&mined(&wallet('a'), 3, 10)).unwrap().seq, 2);
+        let report = verify_chain(&store).unwrap();
+        assert_eq!(report.records, 3);
+        assert!(report.first_broken.is_none());
+    }
+
+    #[test]
+    fn appends_during_a_scan_wait_for_no_lock_and_are_not_visited() {
+        let dir = temp_dir("jsonl-scan-unlocked");
+        let store = open(&dir, ROLL_EACH);
+        store.append(&mined(&wallet('a'), 1, 10)).unwrap();
+        store.append(&mined(&wallet('a'), 2, 10)).unwrap();
+
+        // appending (and rolling) from inside the visitor would deadlock if
+        // the scan held the writer lock
+        let mut seen = Vec::new();
+        store
+            .for_each(&mut |s| {
+                seen.push(s.block_height);
+                store.append(&mined(&wallet('b'), s.block_height + 10, 10)).unwrap();
+            })
+            .unwrap();
+        assert_eq!(seen, vec![1, 2]);
+        assert_eq!(heights(&store), vec![1, 2, 11, 12]);
+    }
+
+    /// Seals the active segment after every record.
+    const ROLL_EACH: SegmentLimits = SegmentLimits { max_bytes: 1, max_age_secs: 0, compress: false };
+
+    fn saved_manifest(dir: &Path) -> Manifest {
+        serde_json::from_slice(&fs::read(dir.join("s.manifest.json")).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn rolls_segments_and_lists_them_in_order() {
+        let dir = temp_dir("jsonl-roll");
+        let store = open(&dir, ROLL_EACH);
+        for h in 1..=3 {
+            store.append(&mined(&wallet('a'), h, 10)).unwrap();
+        }
+
+        let manifest = saved_manifest(&dir);
+        let ids: Vec<u64> = manifest.segments.iter().map(|s| s.id).collect();
+        assert_eq!(ids, vec![1, 2, 3, 4]);
+        let files: Vec<&str> = manifest.segments.iter().map(|s| s.file.as_str()).collect();
+        assert_eq!(files, vec!["s-000001.jsonl", "s-000002.jsonl", "s-000003.jsonl", "s-000004.jsonl"]);
+        let (sealed, active) = manifest.segments.split_at(3);
+        assert!(sealed.iter().all(|s| s.sealed && s.records == 1));
+        assert!(!active[0].sealed);
+        // each segment starts where the previous one ended
+        for pair in manifest.segments.windows(2) {
+            assert_eq!(pair[1].start, pair[0].start + pair[0].bytes);
+        }
+        assert_eq!(store.cursor().unwrap(), active[0].start);
+
+        // reads cross segments in manifest order, also from a cursor
+        assert_eq!(heights(&store), vec![1, 2, 3]);
+        let mut tail = Vec::new();
+        store.for_each_from(manifest.segments[1].start, &mut |s| tail.push(s.block_height)).unwrap();
+        assert_eq!(tail, vec![2, 3]);
+
+        drop(store);
+        let store = open(&dir, ROLL_EACH);
+        assert_eq!(heights(&store), vec![1, 2, 3]);
+        assert_eq!(store.append(&mined(&wallet('a'), 4, 10)).unwrap().seq, 3);
+        assert!(verify_chain(&store).unwrap().first_broken.is_none());
+    }
+
+    #[test]
+    fn compressed_segments_keep_their_offsets() {
+        let dir = temp_dir("jsonl-gzip");
+        let store = open(&dir, ROLL_EACH);
+        for h in 1..=2 {
+            store.append(&mined(&wallet('a'), h, 10)).unwrap();
+        }
+        let cursor = store.cursor().unwrap();
+        compress_segment(&dir, &store.manifest_path(), &store.writer, 1).unwrap();
+
+        let manifest = saved_manifest(&dir);
+        assert_eq!(manifest.segments[0].file, "s-000001.jsonl.gz");
+        assert!(manifest.segments[0].compressed);
+        assert!(!dir.join("s-000001.jsonl").exists());
+        assert_eq!(store.cursor().unwrap(), cursor);
+        assert_eq!(heights(&store), vec![1, 2]);
+
+        drop(store);
+        let store = open(&dir, ROLL_EACH);
+        assert!(store.damage().is_empty());
+        assert_eq!(heights(&store), vec![1, 2]);
+        assert!(verify_chain(&store).unwrap().first_broken.is_none());
+    }
+
+    #[test]
+    fn adopts_a_single_file_log_as_segment_zero() {
+        let dir = temp_dir("jsonl-adopt");
+        let line = encode_line(&serde_json::to_string(&mined(&wallet('a'), 1, 10)).unwrap());
+        fs::write(dir.join("s.jsonl"), line).unwrap();
+
+        let store = open(&dir, NO_ROLL);
+        store.append(&mined(&wallet('a'), 2, 10)).unwrap();
+        let manifest = saved_manifest(&dir);
+        assert_eq!(manifest.segments.len(), 1);
+        assert_eq!((manifest.segments[0].id, manifest.segments[0].file.as_str()), (0, "s.jsonl"));
+        assert_eq!(heights(&store), vec![1, 2]);
+    }
+}
@@ -0,0 +1,146 @@
+// backend/src/store/mod.rs
+
+use std::fmt;
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+
+use crate::handlers::submission::Submission;
+use crate::store::chain::Link;
+
+#[cfg(feature = "proofs")]
+pub mod batch;
+pub mod chain;
+pub mod jsonl;
+pub mod rules_history;
+pub mod schema;
+pub mod sqlite;
+#[cfg(feature = "proofs")]
+pub mod witness_roots;
+
+pub use jsonl::{JsonlStore, SegmentLimits};
+pub use sqlite::SqliteStore;
+
+/// Errors a submission backend can surface.
+#[derive(Debug)]
+pub enum StoreError {
+    Io(std::io::Error),
+    Sqlite(rusqlite::Error),
+    Serde(serde_json::Error),
+    Config(String),
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StoreError::Io(e) => write!(f, "io: {e}"),
+            StoreError::Sqlite(e) => write!(f, "sqlite: {e}"),
+            StoreError::Serde(e) => write!(f, "serde: {e}"),
+            StoreError::Config(msg) => write!(f, "config: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for StoreError {}
+
+impl From<std::io::Error> for StoreError {
+    fn from(e: std::io::Error) -> Self { StoreError::Io(e) }
+}
+impl From<rusqlite::Error> for StoreError {
+    fn from(e: rusqlite::Error) -> Self { StoreError::Sqlite(e) }
+}
+impl From<serde_json::Error> for StoreError {
+    fn from(e: serde_json::Error) -> Self { StoreError::Serde(e) }
+}
+
+/// Durable home for submission records. Implementations are internally
+/// synchronized so handlers can share one behind an `Arc`.
+pub trait SubmissionStore: Send + Sync {
+    /// Stores `sub` chained onto the previous record (see `chain`) and
+    /// returns the sequence number and hash it was given.
+    fn append(&self, sub: &Submission) -> Result<Link, StoreError>;
+
+    fn by_wallet(&self, wallet: &str) -> Result<Vec<Submission>, StoreError>;
+
+    /// The record stored under a client `Idempotency-Key`, if any.
+    fn by_idempotency_key(&self, key: &str) -> Result<Option<Submission>, StoreError>;
+
+    /// Records with `after < date_mined <= until`; either bound may be open.
+    fn by_time_range(
+        &self,
+        after: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<Vec<Submission>, StoreError>;
+
+    /// Opaque position just past the last stored record: the byte offset
+    /// for JSONL, the last rowid for SQLite. Used to pair snapshots with the log.
+    fn cursor(&self) -> Result<u64, StoreError>;
+
+    /// Visits every record after `cursor` in append order without buffering
+    /// the whole log.
+    fn for_each_from(&self, cursor: u64, f: &mut dyn FnMut(Submission)) -> Result<(), StoreError>;
+
+    /// Visits the exact stored JSON text of every record, for hash checks.
+    fn for_each_raw(&self, f: &mut dyn FnMut(&str)) -> Result<(), StoreError>;
+
+    /// Dev-only wipe used by `/reset`.
+    fn clear(&self) -> Result<(), StoreError>;
+
+    /// Damage noticed so far: JSONL lines failing their checksum at open,
+    /// SQLite rows whose body a read could not parse. Such records are
+    /// invisible to every read.
+    fn damage(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn for_each(&self, f: &mut dyn FnMut(Submission)) -> Result<(), StoreError> {
+        self.for_each_from(0, f)
+    }
+
+    fn all(&self) -> Result<Vec<Submission>, StoreError> {
+        let mut out = Vec::new();
+        self.for_each(&mut |s| out.push(s))?;
+        Ok(out)
+    }
+}
+
+/// `SUBMISSION_STORE` (`jsonl` | `sqlite`, default `jsonl`) and the optional
+/// `SUBMISSION_STORE_PATH`.
+pub fn location_from_env() -> (String, Option<String>) {
+    let kind = std::env::var("SUBMISSION_STORE").unwrap_or_else(|_| "jsonl".into());
+    (kind, std::env::var("SUBMISSION_STORE_PATH").ok())
+}
+
+/// Picks a backend from `SUBMISSION_STORE` (`jsonl` | `sqlite`, default `jsonl`).
+/// `SUBMISSION_STORE_PATH` overrides the default file location.
+pub fn open_from_env() -> Result<Arc<dyn SubmissionStore>, StoreError> {
+    let (kind, path) = location_from_env();
+    open(&kind, path, true)
+}
+
+/// Opens an existing log without repairing or creating anything.
+pub fn open_read_only(kind: &str, path: Option<String>) -> Result<Arc<dyn SubmissionStore>, StoreError> {
+    open(kind, path, false)
+}
+
+fn open(kind: &str, path: Option<String>, writable: bool) -> Result<Arc<dyn SubmissionStore>, StoreError> {
+    match kind.trim().to_lowercase().as_str() {
+        "sqlite" => {
+            let path = path.unwrap_or_else(|| "logs/submissions.db".into());
+            Ok(Arc::new(if writable {
+                SqliteStore::open(&path)?
+            } else {
+                SqliteStore::open_read_only(&path)?
+            }))
+        }
+        "jsonl" => {
+            let path = path.unwrap_or_else(|| "logs/submissions.jsonl".into());
+            Ok(Arc::new(if writable {
+                JsonlStore::open(&path, SegmentLimits::from_env())?
+            } else {
+                JsonlStore::open_read_only(&path)?
+            }))
+        }
+        other => Err(StoreError::Config(format!("unknown SUBMISSION_STORE '{other}'"))),
+    }
+}
@@ -0,0 +1,103 @@
+// backend/src/store/rules_history.rs
+
+use std::{
+    collections::BTreeMap,
+    fs::{create_dir_all, File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::engine::game::BonusRules;
+use crate::engine::rules::ScoringRules;
+use crate::store::jsonl::{encode_line, unframe_line};
+use crate::store::StoreError;
+
+/// One scoring table that was in force at some point.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RulesEntry {
+    id: String,
+    rules: ScoringRules,
+    first_used: String,
+}
+
+/// Every scoring table the server has run under, by `ScoringRules::id`, so
+/// replay can keep the bonus state moving under the rules each record was
+/// scored with rather than the ones in force now.
+pub struct RulesHistory {
+    file: Option<Mutex<File>>,
+    tables: Mutex<BTreeMap<String, ScoringRules>>,
+}
+
+/// `RULES_HISTORY_PATH`, default `logs/rules_history.jsonl`.
+pub fn history_path() -> PathBuf {
+    std::env::var("RULES_HISTORY_PATH").unwrap_or_else(|_| "logs/rules_history.jsonl".into()).into()
+}
+
+fn load_tables(path: &Path) -> Result<BTreeMap<String, ScoringRules>, StoreError> {
+    let file = match File::open(path) {
+        Ok(f) => f,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut out = BTreeMap::new();
+    for (i, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() { continue; }
+        match unframe_line(line.trim()).ok().and_then(|j| serde_json::from_str::<RulesEntry>(j).ok()) {
+            Some(e) => { out.entry(e.id).or_insert(e.rules); }
+            None => eprintln!("⚠️  {}: skipping corrupt rules entry on line {}", path.display(), i + 1),
+        }
+    }
+    Ok(out)
+}
+
+impl RulesHistory {
+    pub fn open(path: &Path) -> Result<Self, StoreError> {
+        if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            create_dir_all(dir)?;
+        }
+        let tables = load_tables(path)?;
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self { file: Some(Mutex::new(file)), tables: Mutex::new(tables) })
+    }
+
+    /// Reads the history without creating or writing anything; `record`
+    /// then only remembers tables in memory. Used by the offline tools.
+    pub fn open_read_only(path: &Path) -> Result<Self, StoreError> {
+        Ok(Self { file: None, tables: Mutex::new(load_tables(path)?) })
+    }
+
+    /// Remembers `rules` if its id is new; true if it was.
+    pub fn record(&self, rules: &ScoringRules) -> Result<bool, StoreError> {
+        let id = rules.id();
+        let mut tables = self.tables.lock().unwrap();
+        if tables.contains_key(&id) {
+            return Ok(false);
+        }
+        if let Some(file) = &self.file {
+            let entry = RulesEntry { id: id.clone(), rules: rules.clone(), first_used: Utc::now().to_rfc3339() };
+            let mut file = file.lock().unwrap();
+            file.write_all(encode_line(&serde_json::to_string(&entry)?).as_bytes())?;
+            file.sync_data()?;
+        }
+        tables.insert(id, rules.clone());
+        Ok(true)
+    }
+
+    /// Bonus rules of the table with this id, if it was ever recorded.
+    pub fn bonus_rules(&self, id: &str) -> Option<BonusRules> {
+        self.tables.lock().unwrap().get(id).map(ScoringRules::bonus_rules)
+    }
+
+    pub fn len(&self) -> usize {
+        self.tables.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
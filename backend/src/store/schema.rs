@@ -0,0 +1,172 @@
+// backend/src/store/schema.rs
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use serde::Deserialize;
+
+use crate::engine::game::Bonus;
+use crate::engine::tier::TierChange;
+use crate::handlers::submission::Submission;
+
+/// Version written on every new record. Bump it only when existing fields
+/// change shape or meaning, since a build refuses records newer than the
+/// version it knows; optional fields that default to absent are added
+/// without a bump, and older records simply lack them.
+///
+/// 1. free-form `date_mined` string, no `schema_version` field
+/// 2. `date_mined` is RFC 3339 UTC
+///
+/// Added since without a bump: `reverts` (revert records written when a
+/// reorg orphans a block), `tier_change`, `rules`, `season`, `bonuses`,
+/// `achievement` (achievement unlock records) and `guild` (guild join/leave
+/// records, and the guild an event credited).
+pub const SCHEMA_VERSION: u16 = 2;
+
+fn legacy_version() -> u16 { 1 }
+
+/// A `Submission` as it may appear on disk or on the wire, any version.
+/// `Submission` deserializes through this and `migrate`, so old records
+/// come out upgraded; the stored text (and its chain hash) is left alone.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SubmissionRecord {
+    pub wallet: String,
+    pub score: u32,
+    pub mu_level: u8,
+    pub block_height: u64,
+    #[serde(default)]
+    pub date_mined: String,
+    pub event_type: String,
+    #[serde(default = "legacy_version")]
+    pub schema_version: u16,
+    #[serde(default)]
+    pub seq: Option<u64>,
+    #[serde(default)]
+    pub prev_hash: Option<String>,
+    #[serde(default)]
+    pub idempotency_key: Option<String>,
+    #[serde(default)]
+    pub reverts: Option<u64>,
+    #[serde(default)]
+    pub tier_change: Option<TierChange>,
+    #[serde(default)]
+    pub rules: Option<String>,
+    #[serde(default)]
+    pub season: Option<String>,
+    #[serde(default)]
+    pub bonuses: Option<Vec<Bonus>>,
+    #[serde(default)]
+    pub achievement: Option<String>,
+    #[serde(default)]
+    pub guild: Option<String>,
+}
+
+/// Parses every date format the log has seen: RFC 3339 from the handlers,
+/// `%Y-%m-%d %H:%M:%S` from the simulator, and bare dates. Zoneless values
+/// are taken as UTC, which is what their writers used.
+pub fn parse_timestamp(s: &str) -> Option<DateTime<Utc>> {
+    let s = s.trim();
+    if let Ok(dt) = DateTime::parse_from_rfc3339(s) {
+        return Some(dt.with_timezone(&Utc));
+    }
+    for fmt in ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f"] {
+        if let Ok(naive) = NaiveDateTime::parse_from_str(s, fmt) {
+            return Some(naive.and_utc());
+        }
+    }
+    NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .ok()
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .map(|naive| naive.and_utc())
+}
+
+/// Fixed-width form whose string order is time order; used where a
+/// timestamp has to be compared as text (SQLite index column).
+pub fn time_key(dt: &DateTime<Utc>) -> String {
+    dt.format("%Y-%m-%dT%H:%M:%S%.9fZ").to_string()
+}
+
+/// Upgrades a record of any known version to `SCHEMA_VERSION`.
+pub fn migrate(rec: SubmissionRecord) -> Result<Submission, String> {
+    if rec.schema_version > SCHEMA_VERSION {
+        return Err(format!(
+            "record has schema_version {}, this build reads up to {SCHEMA_VERSION}",
+            rec.schema_version
+        ));
+    }
+    // v1 -> v2: free-form date string to a UTC timestamp
+    let date_mined = parse_timestamp(&rec.date_mined)
+        .ok_or_else(|| format!("unrecognized date_mined '{}'", rec.date_mined))?;
+
+    Ok(Submission {
+        wallet: rec.wallet,
+        score: rec.score,
+        mu_level: rec.mu_level,
+        block_height: rec.block_height,
+        date_mined,
+        event_type: rec.event_type,
+        schema_version: SCHEMA_VERSION,
+        seq: rec.seq,
+        prev_hash: rec.prev_hash,
+        idempotency_key: rec.idempotency_key,
+        reverts: rec.reverts,
+        tier_change: rec.tier_change,
+        rules: rec.rules,
+        season: rec.season,
+        bonuses: rec.bonuses,
+        achievement: rec.achievement,
+        guild: rec.guild,
+    })
+}
+
+impl TryFrom<SubmissionRecord> for Submission {
+    type Error = String;
+
+    fn try_from(rec: SubmissionRecord) -> Result<Self, Self::Error> {
+        migrate(rec)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn record(date_mined: &str, version: Option<u16>) -> SubmissionRecord {
+        let mut json = serde_json::json!({
+            "wallet": "ab", "score": 15, "mu_level": 18, "block_height": 7,
+            "date_mined": date_mined, "event_type": "mined"
+        });
+        if let Some(v) = version {
+            json["schema_version"] = v.into();
+        }
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn simulator_records_migrate_to_utc_timestamps() {
+        let sub = migrate(record("2025-03-04 05:06:07", None)).unwrap();
+        assert_eq!(sub.date_mined, Utc.with_ymd_and_hms(2025, 3, 4, 5, 6, 7).unwrap());
+        assert_eq!(sub.schema_version, SCHEMA_VERSION);
+        assert_eq!((sub.wallet.as_str(), sub.score, sub.block_height), ("ab", 15, 7));
+
+        let bare = migrate(record("2025-03-04", None)).unwrap();
+        assert_eq!(bare.date_mined, Utc.with_ymd_and_hms(2025, 3, 4, 0, 0, 0).unwrap());
+        let offset = migrate(record("2025-03-04T07:06:07+02:00", Some(SCHEMA_VERSION))).unwrap();
+        assert_eq!(offset.date_mined, sub.date_mined);
+    }
+
+    #[test]
+    fn unreadable_dates_and_newer_versions_are_refused() {
+        let err = migrate(record("last tuesday", None)).unwrap_err();
+        assert!(err.contains("unrecognized date_mined"), "{err}");
+        let err = migrate(record("2025-03-04 05:06:07", Some(SCHEMA_VERSION + 1))).unwrap_err();
+        assert!(err.contains("schema_version"), "{err}");
+    }
+
+    #[test]
+    fn time_keys_sort_like_the_times_they_encode() {
+        let early = parse_timestamp("2025-03-04 05:06:07").unwrap();
+        let late = parse_timestamp("2025-03-04T05:06:07.5Z").unwrap();
+        assert!(time_key(&early) < time_key(&late));
+        assert_eq!(parse_timestamp(&time_key(&late)), Some(late));
+    }
+}
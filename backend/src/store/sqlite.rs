@@ -0,0 +1,255 @@
+// backend/src/store/sqlite.rs
+
+use std::{collections::{btree_map::Entry, BTreeMap}, fs::create_dir_all, path::Path, sync::Mutex};
+
+use chrono::{DateTime, Utc};
+use rusqlite::{params, Connection, OpenFlags};
+
+use crate::handlers::submission::Submission;
+use crate::store::chain::{self, ChainTail, Link};
+use crate::store::schema::time_key;
+use crate::store::{StoreError, SubmissionStore};
+
+/// Embedded SQLite backend. The full record lives in `body` as JSON; the
+/// columns we filter on are pulled out and indexed. `date_mined` holds
+/// `schema::time_key` so that text comparison is time comparison.
+pub struct SqliteStore {
+    conn: Mutex<Connection>,
+    tail: Mutex<ChainTail>,
+    /// Rows whose body did not parse, by id, as reads came across them.
+    damage: Mutex<BTreeMap<i64, String>>,
+}
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS submissions (
+        id           INTEGER PRIMARY KEY AUTOINCREMENT,
+        wallet       TEXT    NOT NULL,
+        block_height INTEGER NOT NULL,
+        date_mined   TEXT    NOT NULL,
+        event_type   TEXT    NOT NULL,
+        body         TEXT    NOT NULL,
+        idempotency_key TEXT
+    );
+    CREATE INDEX IF NOT EXISTS idx_submissions_wallet ON submissions (wallet);
+    CREATE INDEX IF NOT EXISTS idx_submissions_date   ON submissions (date_mined);
+";
+
+/// Brings an existing database up to `user_version` 2:
+/// 1. `date_mined` held whatever string the client sent; rewrite as `time_key`s
+/// 2. `idempotency_key` column with a unique index
+fn migrate(conn: &Connection) -> Result<(), StoreError> {
+    let version: i64 = conn.query_row("PRAGMA user_version", [], |r| r.get(0))?;
+    if version >= 2 {
+        return Ok(());
+    }
+    let tx = conn.unchecked_transaction()?;
+    if version < 1 {
+        let mut select = tx.prepare("SELECT id, body FROM submissions")?;
+        let mut update = tx.prepare("UPDATE submissions SET date_mined = ?1 WHERE id = ?2")?;
+        let rows = select.query_map([], |r| Ok((r.get::<_, i64>(0)?, r.get::<_, String>(1)?)))?;
+        for row in rows {
+            let (id, body) = row?;
+            match serde_json::from_str::<Submission>(&body) {
+                Ok(s) => { update.execute(params![time_key(&s.date_mined), id])?; }
+                Err(e) => eprintln!("⚠️  submissions row {id}: cannot migrate date_mined: {e}"),
+            }
+        }
+    }
+    let has_key: bool = tx.query_row(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info('submissions') WHERE name = 'idempotency_key'",
+        [],
+        |r| r.get(0),
+    )?;
+    if !has_key {
+        tx.execute_batch("ALTER TABLE submissions ADD COLUMN idempotency_key TEXT")?;
+    }
+    tx.execute_batch(
+        "CREATE UNIQUE INDEX IF NOT EXISTS idx_submissions_idempotency
+             ON submissions (idempotency_key) WHERE idempotency_key IS NOT NULL;
+         PRAGMA user_version = 2;",
+    )?;
+    tx.commit()?;
+    Ok(())
+}
+
+impl SqliteStore {
+    pub fn open(path: &str) -> Result<Self, StoreError> {
+        if let Some(dir) = Path::new(path).parent() {
+            create_dir_all(dir)?;
+        }
+        let conn = Connection::open(path)?;
+        conn.execute_batch("PRAGMA journal_mode = WAL;")?;
+        conn.execute_batch(SCHEMA)?;
+        migrate(&conn)?;
+        Self::with_conn(conn)
+    }
+
+    /// Opens an existing database without creating or migrating it.
+    pub fn open_read_only(path: &str) -> Result<Self, StoreError> {
+        Self::with_conn(Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?)
+    }
+
+    fn with_conn(conn: Connection) -> Result<Self, StoreError> {
+        let store = Self {
+            conn: Mutex::new(conn),
+            tail: Mutex::new(ChainTail::genesis()),
+            damage: Mutex::new(BTreeMap::new()),
+        };
+        let tail = chain::scan_tail(&store)?;
+        *store.tail.lock().unwrap() = tail;
+        Ok(store)
+    }
+
+    /// Runs a `SELECT id, body` query. Rows that do not parse are skipped
+    /// and noted in `damage`, as `for_each_from` does.
+    fn query(&self, sql: &str, args: &[&dyn rusqlite::ToSql]) -> Result<Vec<Submission>, StoreError> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(sql)?;
+        let rows = stmt.query_map(args, |r| Ok((r.get::<_, i64>(0)?, r.get::<_, String>(1)?)))?;
+        let mut out = Vec::new();
+        for row in rows {
+            let (id, body) = row?;
+            match serde_json::from_str(&body) {
+                Ok(sub) => out.push(sub),
+                Err(e) => self.note_unreadable(id, &e),
+            }
+        }
+        Ok(out)
+    }
+
+    /// Warns about an unreadable row the first time a read skips it.
+    fn note_unreadable(&self, id: i64, e: &serde_json::Error) {
+        if let Entry::Vacant(slot) = self.damage.lock().unwrap().entry(id) {
+            let msg = format!("submissions row {id}: skipping unreadable body: {e}");
+            eprintln!("⚠️  {msg}");
+            slot.insert(msg);
+        }
+    }
+}
+
+impl SubmissionStore for SqliteStore {
+    fn append(&self, sub: &Submission) -> Result<Link, StoreError> {
+        let mut tail = self.tail.lock().unwrap();
+        let (rec, body) = tail.link(sub)?;
+        let conn = self.conn.lock().unwrap();
+        conn.execute(
+            "INSERT INTO submissions (wallet, block_height, date_mined, event_type, body, idempotency_key)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                rec.wallet,
+                rec.block_height as i64,
+                time_key(&rec.date_mined),
+                rec.event_type,
+                body,
+                rec.idempotency_key
+            ],
+        )?;
+        Ok(tail.advance(&body))
+    }
+
+    fn by_wallet(&self, wallet: &str) -> Result<Vec<Submission>, StoreError> {
+        self.query(
+            "SELECT id, body FROM submissions WHERE wallet = ?1 ORDER BY id",
+            &[&wallet],
+        )
+    }
+
+    fn by_idempotency_key(&self, key: &str) -> Result<Option<Submission>, StoreError> {
+        Ok(self
+            .query("SELECT id, body FROM submissions WHERE idempotency_key = ?1", &[&key])?
+            .pop())
+    }
+
+    fn by_time_range(
+        &self,
+        after: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<Vec<Submission>, StoreError> {
+        let (after, until) = (after.as_ref().map(time_key), until.as_ref().map(time_key));
+        self.query(
+            "SELECT id, body FROM submissions
+             WHERE (?1 IS NULL OR date_mined > ?1) AND (?2 IS NULL OR date_mined <= ?2)
+             ORDER BY id",
+            &[&after, &until],
+        )
+    }
+
+    fn cursor(&self) -> Result<u64, StoreError> {
+        let conn = self.conn.lock().unwrap();
+        let last: i64 = conn.query_row("SELECT COALESCE(MAX(id), 0) FROM submissions", [], |r| r.get(0))?;
+        Ok(last as u64)
+    }
+
+    fn for_each_raw(&self, f: &mut dyn FnMut(&str)) -> Result<(), StoreError> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT body FROM submissions ORDER BY id")?;
+        let mut rows = stmt.query([])?;
+        while let Some(row) = rows.next()? {
+            let body: String = row.get(0)?;
+            f(&body);
+        }
+        Ok(())
+    }
+
+    fn for_each_from(&self, cursor: u64, f: &mut dyn FnMut(Submission)) -> Result<(), StoreError> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare("SELECT id, body FROM submissions WHERE id > ?1 ORDER BY id")?;
+        let mut rows = stmt.query([cursor as i64])?;
+        while let Some(row) = rows.next()? {
+            let id: i64 = row.get(0)?;
+            let body: String = row.get(1)?;
+            match serde_json::from_str(&body) {
+                Ok(sub) => f(sub),
+                Err(e) => self.note_unreadable(id, &e),
+            }
+        }
+        Ok(())
+    }
+
+    fn damage(&self) -> Vec<String> {
+        self.damage.lock().unwrap().values().cloned().collect()
+    }
+
+    fn clear(&self) -> Result<(), StoreError> {
+        // same lock order as `append`: tail, then conn
+        let mut tail = self.tail.lock().unwrap();
+        let conn = self.conn.lock().unwrap();
+        conn.execute("DELETE FROM submissions", [])?;
+        *tail = ChainTail::genesis();
+        self.damage.lock().unwrap().clear();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::{mined, temp_dir, wallet};
+
+    #[test]
+    fn queries_skip_unreadable_rows_and_report_them() {
+        let dir = temp_dir("sqlite-damage");
+        let store = SqliteStore::open(dir.join("s.db").to_str().unwrap()).unwrap();
+        let a = wallet('a');
+        store.append(&mined(&a, 1, 10)).unwrap();
+        store
+            .conn
+            .lock()
+            .unwrap()
+            .execute(
+                "INSERT INTO submissions (wallet, block_height, date_mined, event_type, body)
+                 VALUES (?1, 2, '2025-01-01', 'mined', '{not json')",
+                [&a],
+            )
+            .unwrap();
+        store.append(&mined(&a, 3, 10)).unwrap();
+        assert!(store.damage().is_empty());
+
+        let heights = |subs: Vec<Submission>| subs.iter().map(|s| s.block_height).collect::<Vec<_>>();
+        assert_eq!(heights(store.by_wallet(&a).unwrap()), vec![1, 3]);
+        assert_eq!(heights(store.by_time_range(None, None).unwrap()), vec![1, 3]);
+        // reported once, however many reads skip it
+        assert_eq!(store.damage().len(), 1);
+        assert!(store.damage()[0].starts_with("submissions row 2:"));
+    }
+}
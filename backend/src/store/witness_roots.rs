@@ -0,0 +1,276 @@
+// backend/src/store/witness_roots.rs
+
+use std::{
+    collections::BTreeMap,
+    fs::{create_dir_all, File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+
+use crate::engine::merkle::{compute_leaf_from_wallet, compute_merkle_proof, compute_merkle_root, merkle_depth};
+use crate::state::pki::PubKey;
+use crate::store::jsonl::{encode_line, unframe_line};
+use crate::store::StoreError;
+
+/// The witness set of one superblock and the Merkle root over it. Leaves
+/// are `compute_leaf_from_wallet` of each wallet, in the published order.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WitnessRoot {
+    pub block_height: u64,
+    pub root: String,
+    pub wallets: Vec<String>,
+    pub published_at: String,
+}
+
+/// A witness proof for one wallet, in the shape `/submit/witness` takes.
+#[derive(Debug, Clone, Serialize)]
+pub struct WitnessProof {
+    pub block_height: u64,
+    pub index: usize,
+    pub siblings: Vec<String>,
+    pub path: String,
+    pub root: String,
+}
+
+/// What `publish` did.
+#[derive(Debug, Clone)]
+pub enum Publish {
+    Added(WitnessRoot),
+    /// The same set was already published for the block.
+    Unchanged(WitnessRoot),
+    /// A different set was already published; roots are only replaced once
+    /// a reorg retired them, since witnesses may have been accepted against it.
+    Conflict(WitnessRoot),
+}
+
+/// One line of the roots file: a published root, or a reorg retiring every
+/// root at or above a height.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum RootsLine {
+    Root(WitnessRoot),
+    Retire { retire_from: u64, retired_at: String },
+}
+
+struct RootsState {
+    file: File,
+    roots: BTreeMap<u64, WitnessRoot>,
+}
+
+/// Published witness-set roots by block height, kept in memory and in an
+/// append-only file next to the submission log.
+pub struct WitnessRoots {
+    state: Mutex<RootsState>,
+}
+
+fn load_roots(path: &Path) -> Result<BTreeMap<u64, WitnessRoot>, StoreError> {
+    let file = match File::open(path) {
+        Ok(f) => f,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut out = BTreeMap::new();
+    for (i, line) in BufReader::new(file).lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() { continue; }
+        match unframe_line(line.trim()).ok().and_then(|j| serde_json::from_str::<RootsLine>(j).ok()) {
+            Some(RootsLine::Root(r)) => { out.entry(r.block_height).or_insert(r); }
+            Some(RootsLine::Retire { retire_from, .. }) => { out.split_off(&retire_from); }
+            None => eprintln!("⚠️  {}: skipping corrupt witness root on line {}", path.display(), i + 1),
+        }
+    }
+    Ok(out)
+}
+
+impl WitnessRoots {
+    pub fn open(path: &str) -> Result<Self, StoreError> {
+        let path = PathBuf::from(path);
+        if let Some(dir) = path.parent() {
+            create_dir_all(dir)?;
+        }
+        let roots = load_roots(&path)?;
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        Ok(Self { state: Mutex::new(RootsState { file, roots }) })
+    }
+
+    /// `WITNESS_ROOTS_PATH` (default `logs/witness_roots.jsonl`).
+    pub fn open_from_env() -> Result<Self, StoreError> {
+        let path = std::env::var("WITNESS_ROOTS_PATH").unwrap_or_else(|_| "logs/witness_roots.jsonl".into());
+        Self::open(&path)
+    }
+
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().roots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Builds the root over `wallets` and stores it for `block_height`.
+    pub fn publish(&self, block_height: u64, wallets: &[PubKey]) -> Result<Publish, StoreError> {
+        let leaves: Vec<[u8; 64]> = wallets.iter().map(compute_leaf_from_wallet).collect();
+        let root = WitnessRoot {
+            block_height,
+            root: hex::encode(compute_merkle_root(leaves)),
+            wallets: wallets.iter().map(|w| hex::encode(w.as_bytes())).collect(),
+            published_at: Utc::now().to_rfc3339(),
+        };
+
+        let mut st = self.state.lock().unwrap();
+        if let Some(old) = st.roots.get(&block_height) {
+            return Ok(if old.root == root.root {
+                Publish::Unchanged(old.clone())
+            } else {
+                Publish::Conflict(old.clone())
+            });
+        }
+        let line = encode_line(&serde_json::to_string(&RootsLine::Root(root.clone()))?);
+        st.file.write_all(line.as_bytes())?;
+        st.file.sync_data()?;
+        st.roots.insert(block_height, root.clone());
+        Ok(Publish::Added(root))
+    }
+
+    /// Drops the roots of every block at or above `from_height` after a
+    /// reorg orphaned them, so the replacement blocks can be published.
+    /// Returns how many were retired; nothing is written if none were.
+    pub fn retire_from(&self, from_height: u64) -> Result<usize, StoreError> {
+        let mut st = self.state.lock().unwrap();
+        let retired = st.roots.range(from_height..).count();
+        if retired == 0 {
+            return Ok(0);
+        }
+        let line = RootsLine::Retire { retire_from: from_height, retired_at: Utc::now().to_rfc3339() };
+        st.file.write_all(encode_line(&serde_json::to_string(&line)?).as_bytes())?;
+        st.file.sync_data()?;
+        st.roots.split_off(&from_height);
+        Ok(retired)
+    }
+
+    pub fn get(&self, block_height: u64) -> Option<WitnessRoot> {
+        self.state.lock().unwrap().roots.get(&block_height).cloned()
+    }
+
+    /// The trusted root witnesses of `block_height` are checked against,
+    /// and the number of wallets (leaves) under it.
+    pub fn root_for(&self, block_height: u64) -> Option<([u8; 64], usize)> {
+        let root = self.get(block_height)?;
+        Some((hex::decode(root.root).ok()?.try_into().ok()?, root.wallets.len()))
+    }
+
+    /// Proof that `wallet` is in the block's witness set, or `None` if it is
+    /// not (or no root was published).
+    pub fn proof_for(&self, block_height: u64, wallet: &PubKey) -> Option<WitnessProof> {
+        let root = self.get(block_height)?;
+        let wallet = hex::encode(wallet.as_bytes());
+        let index = root.wallets.iter().position(|w| *w == wallet)?;
+        let leaves: Vec<[u8; 64]> = root
+            .wallets
+            .iter()
+            .filter_map(|w| hex::decode(w).ok()?.try_into().ok())
+            .map(|pk: [u8; 32]| compute_leaf_from_wallet(&PubKey::new(pk)))
+            .collect();
+        let depth = merkle_depth(leaves.len());
+        let siblings = compute_merkle_proof(leaves, index);
+        // bit i of the index is the node's side at level i, leaf level first
+        let path = (0..depth).map(|i| if index >> i & 1 == 1 { '1' } else { '0' }).collect();
+        Some(WitnessProof {
+            block_height,
+            index,
+            siblings: siblings.iter().map(|s| format!("0x{}", hex::encode(s))).collect(),
+            path,
+            root: format!("0x{}", root.root),
+        })
+    }
+
+    pub fn clear(&self) -> Result<(), StoreError> {
+        let mut st = self.state.lock().unwrap();
+        st.file.set_len(0)?;
+        st.file.sync_all()?;
+        st.roots.clear();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::merkle::verify_merkle_proof;
+    use crate::testutil::temp_dir;
+
+    fn wallets(bytes: &[u8]) -> Vec<PubKey> {
+        bytes.iter().map(|b| PubKey::new([*b; 32])).collect()
+    }
+
+    fn open(dir: &Path) -> WitnessRoots {
+        WitnessRoots::open(dir.join("w.jsonl").to_str().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn roots_are_kept_and_never_replaced() {
+        let dir = temp_dir("witness-roots");
+        let roots = open(&dir);
+        assert!(matches!(roots.publish(10, &wallets(&[1, 2, 3])).unwrap(), Publish::Added(_)));
+        assert!(matches!(roots.publish(10, &wallets(&[1, 2, 3])).unwrap(), Publish::Unchanged(_)));
+        let Publish::Conflict(kept) = roots.publish(10, &wallets(&[1, 2, 4])).unwrap() else {
+            panic!("a different set must conflict");
+        };
+        assert_eq!(kept, roots.get(10).unwrap());
+        drop(roots);
+
+        let roots = open(&dir);
+        assert_eq!(roots.len(), 1);
+        assert_eq!(roots.get(10), Some(kept));
+    }
+
+    #[test]
+    fn proofs_round_trip_through_the_published_root() {
+        let dir = temp_dir("witness-proofs");
+        let roots = open(&dir);
+        let set = wallets(&[1, 2, 3, 4, 5]);
+        roots.publish(10, &set).unwrap();
+        let (root, count) = roots.root_for(10).unwrap();
+        assert_eq!(count, 5);
+
+        for (i, wallet) in set.iter().enumerate() {
+            let proof = roots.proof_for(10, wallet).unwrap();
+            assert_eq!(proof.index, i);
+            assert_eq!(proof.root, format!("0x{}", hex::encode(root)));
+            let siblings: Vec<[u8; 64]> = proof
+                .siblings
+                .iter()
+                .map(|s| hex::decode(s.trim_start_matches("0x")).unwrap().try_into().unwrap())
+                .collect();
+            assert!(verify_merkle_proof(compute_leaf_from_wallet(wallet), siblings, root, i, count));
+        }
+        assert!(roots.proof_for(10, &PubKey::new([9; 32])).is_none());
+        assert!(roots.proof_for(11, &set[0]).is_none());
+    }
+
+    #[test]
+    fn reorg_retires_roots_for_good() {
+        let dir = temp_dir("witness-retire");
+        let roots = open(&dir);
+        for h in [8, 10, 12] {
+            roots.publish(h, &wallets(&[1, 2])).unwrap();
+        }
+        assert_eq!(roots.retire_from(10).unwrap(), 2);
+        assert_eq!(roots.retire_from(10).unwrap(), 0);
+        assert!(roots.get(10).is_none());
+
+        // the replacement block publishes its own set
+        assert!(matches!(roots.publish(10, &wallets(&[3, 4])).unwrap(), Publish::Added(_)));
+        let replaced = roots.get(10).unwrap();
+        drop(roots);
+
+        let roots = open(&dir);
+        let heights: Vec<u64> = [8, 10, 12].into_iter().filter(|h| roots.get(*h).is_some()).collect();
+        assert_eq!(heights, vec![8, 10]);
+        assert_eq!(roots.get(10), Some(replaced));
+    }
+}
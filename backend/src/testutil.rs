@@ -0,0 +1,158 @@
+// backend/src/testutil.rs
+// Helpers shared by the unit tests.
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use axum::{extract::State, http::{HeaderMap, HeaderValue}, Json};
+use chrono::{TimeZone, Utc};
+
+use crate::engine::{achievement::AchievementBook, rules::RulesHandle, season::SeasonCalendar, tier::TierTable};
+use crate::handlers::health::{HealthState, Metrics, StatusKind};
+use crate::handlers::submission::{handle_submission, ApiError, AppState, Submission};
+use crate::handlers::submit_mine::{submit_mine, MineReq};
+use crate::handlers::submit_witness::{submit_witness, ProofJson, WitnessReq};
+use crate::state::{seasons::Seasons, Guilds, Sessions};
+use crate::store::schema::SCHEMA_VERSION;
+use crate::store::{rules_history::RulesHistory, JsonlStore, SegmentLimits};
+
+/// A fresh, empty directory under the system temp dir.
+pub fn temp_dir(name: &str) -> PathBuf {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    let n = NEXT.fetch_add(1, Ordering::Relaxed);
+    let dir = std::env::temp_dir().join(format!("mue-test-{name}-{}-{n}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("create temp dir");
+    dir
+}
+
+/// Hex wallet made of one repeated digit, e.g. `wallet('a')`.
+pub fn wallet(c: char) -> String {
+    std::iter::repeat_n(c, 64).collect()
+}
+
+/// A current-schema mined record, dated `block_height` minutes into 2025.
+pub fn mined(wallet: &str, block_height: u64, score: u32) -> Submission {
+    Submission {
+        wallet: wallet.to_string(),
+        score,
+        mu_level: 18,
+        block_height,
+        date_mined: Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap()
+            + chrono::Duration::minutes(block_height as i64),
+        event_type: "mined".into(),
+        schema_version: SCHEMA_VERSION,
+        seq: None,
+        prev_hash: None,
+        idempotency_key: None,
+        reverts: None,
+        tier_change: None,
+        rules: None,
+        season: None,
+        bonuses: None,
+        achievement: None,
+        guild: None,
+    }
+}
+
+/// Secret the handlers are called with; `app_state` sets `MUE_SECRET` to it.
+pub const SECRET: &str = "test-secret";
+
+/// Fresh server state over a JSONL log in `dir`, with the built-in rules,
+/// tiers and achievements and no seasons.
+pub fn app_state(dir: &Path) -> AppState {
+    std::env::set_var("MUE_SECRET", SECRET);
+    let limits = SegmentLimits { max_bytes: u64::MAX, max_age_secs: 0, compress: false };
+    AppState {
+        store: Arc::new(JsonlStore::open(dir.join("s.jsonl").to_str().unwrap(), limits).unwrap()),
+        sessions: Arc::new(Sessions::new()),
+        tiers: Arc::new(TierTable::default()),
+        rules: Arc::new(RulesHandle::from_env().unwrap()),
+        rules_history: Arc::new(RulesHistory::open(&dir.join("rules_history.jsonl")).unwrap()),
+        seasons: Arc::new(Seasons::new(SeasonCalendar::default(), dir.join("seasons"))),
+        achievements: Arc::new(AchievementBook::default()),
+        guilds: Arc::new(Guilds::new()),
+        #[cfg(feature = "proofs")]
+        witness_roots: Arc::new(
+            crate::store::witness_roots::WitnessRoots::open(dir.join("w.jsonl").to_str().unwrap()).unwrap(),
+        ),
+    }
+}
+
+/// Calls `/submit/mine` for `wallet`, with an `Idempotency-Key` if given.
+pub async fn mine(
+    app: &AppState,
+    wallet: &str,
+    key: Option<&str>,
+    mu_level: u8,
+    block_height: u64,
+) -> Result<serde_json::Value, ApiError> {
+    let mut headers = HeaderMap::new();
+    headers.insert("X-MUE-KEY", HeaderValue::from_static(SECRET));
+    if let Some(key) = key {
+        headers.insert("Idempotency-Key", HeaderValue::from_str(key).unwrap());
+    }
+    let req = MineReq { wallet: wallet.to_string(), mu_level, block_height };
+    submit_mine(State(app.clone()), headers, Json(req)).await.map(|Json(v)| v)
+}
+
+/// Calls the legacy `/submit` with `record` as the JSON body.
+pub async fn submit(app: &AppState, record: serde_json::Value) -> Result<serde_json::Value, ApiError> {
+    let mut headers = HeaderMap::new();
+    headers.insert("X-MUE-KEY", HeaderValue::from_static(SECRET));
+    let record = serde_json::from_value(record).expect("a SubmissionRecord");
+    handle_submission(State(app.clone()), headers, Json(record)).await.map(|Json(v)| v)
+}
+
+/// Calls `/submit/witness` for `wallet` on `block_height`. With `proofs`, a
+/// witness set of just `wallet` is published for the block unless it has one.
+pub async fn witness(
+    app: &AppState,
+    wallet: &str,
+    mu_level: u8,
+    block_height: u64,
+) -> Result<serde_json::Value, ApiError> {
+    let mut headers = HeaderMap::new();
+    headers.insert("X-MUE-KEY", HeaderValue::from_static(SECRET));
+    #[cfg(feature = "proofs")]
+    let proof = {
+        let pubkey = crate::handlers::submission::decode_pubkey_from_hex(wallet).unwrap();
+        if app.witness_roots.get(block_height).is_none() {
+            app.witness_roots.publish(block_height, std::slice::from_ref(&pubkey)).unwrap();
+        }
+        let p = app.witness_roots.proof_for(block_height, &pubkey).expect("wallet is not in the witness set");
+        ProofJson { siblings: p.siblings, path: p.path, root: Some(p.root) }
+    };
+    #[cfg(not(feature = "proofs"))]
+    let proof = ProofJson { siblings: Vec::new(), path: String::new(), root: None };
+    let req = WitnessReq { wallet: wallet.to_string(), mu_level, proof, block_height };
+    submit_witness(State(app.clone()), headers, Json(req)).await.map(|Json(v)| v)
+}
+
+/// Admin token of `health_state`, sent as `x-admin-token` by `admin_headers`.
+pub const ADMIN_TOKEN: &str = "test-admin";
+
+/// A healthy node with `ADMIN_TOKEN` configured, for the admin handlers.
+pub fn health_state() -> HealthState {
+    HealthState {
+        metrics: Arc::new(std::sync::RwLock::new(Metrics {
+            db_up: true,
+            node_reachable: true,
+            unreachable_streak: 0,
+            indexer_lag_sec: 0,
+            queue_depth: 0,
+            rpc_error_rate: 0.0,
+        })),
+        last_status: Arc::new(std::sync::RwLock::new(StatusKind::Ready)),
+        last_change_at: Arc::new(std::sync::RwLock::new(std::time::Instant::now())),
+        is_prod: false,
+        admin_token: Some(ADMIN_TOKEN.into()),
+    }
+}
+
+pub fn admin_headers() -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert("x-admin-token", HeaderValue::from_static(ADMIN_TOKEN));
+    headers
+}
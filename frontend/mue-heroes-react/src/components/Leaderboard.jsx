@@ -59,6 +59,7 @@ export default function Leaderboard({ entries: injected }) {
                         <th>Hero Tag</th>
                         <th>μ-Level</th>
                         <th>Score</th>
+                        <th>Tier</th>
                         <th>Block Height</th>
                         <th>Date Mined</th>
                     </tr>
@@ -66,13 +67,13 @@ export default function Leaderboard({ entries: injected }) {
                 <tbody>
                     {loading ? (
                         <tr>
-                            <td colSpan={6} className="empty">
+                            <td colSpan={7} className="empty">
                                 Loading leaderboard…
                             </td>
                         </tr>
                     ) : visible.length === 0 ? (
                         <tr>
-                            <td colSpan={6} className="empty">
+                            <td colSpan={7} className="empty">
                                 No entries yet
                             </td>
                         </tr>
@@ -85,6 +86,7 @@ export default function Leaderboard({ entries: injected }) {
                                 </td>
                                 <td>{e.mu_level ?? "—"}</td>
                                 <td>{e.score ?? "—"}</td>
+                                <td>{e.medal ? `${e.medal} ` : ""}{e.tier ?? "—"}</td>
                                 <td>{e.block_height ?? "—"}</td>
                                 <td>
                                     {e.date_mined ? new Date(e.date_mined).toLocaleString() : "—"}